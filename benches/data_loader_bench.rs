@@ -26,7 +26,7 @@ fn benchmark_data_loader(c: &mut Criterion) {
     b.iter(|| {
       tokio::runtime::Runtime::new().unwrap().spawn(async {
         let client = MockHttpClient { request_count: Arc::new(AtomicUsize::new(0)) };
-        let loader = HttpDataLoader { client: client.clone(), batched: None };
+        let loader = HttpDataLoader { client: Arc::new(client.clone()), batched: None, response_format: None };
         let loader = loader.to_data_loader(Batch::default().delay(1));
 
         let request1 = reqwest::Request::new(reqwest::Method::GET, "http://example.com/1".parse().unwrap());
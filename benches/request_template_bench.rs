@@ -6,6 +6,7 @@ use hyper::HeaderMap;
 use serde_json::json;
 use tailcall::endpoint::Endpoint;
 use tailcall::has_headers::HasHeaders;
+use tailcall::has_uploads::HasUploads;
 use tailcall::path_string::PathString;
 use tailcall::request_template::RequestTemplate;
 
@@ -30,6 +31,7 @@ impl HasHeaders for Context {
     &self.headers
   }
 }
+impl HasUploads for Context {}
 fn benchmark_to_request(c: &mut Criterion) {
   let tmpl_mustache = RequestTemplate::try_from(Endpoint::new(
     "http://localhost:3000/{{args.b}}?a={{args.a}}&b={{args.b}}&c={{args.c}}".to_string(),
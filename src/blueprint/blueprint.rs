@@ -10,6 +10,7 @@ use super::GlobalTimeout;
 use crate::blueprint::server::Server;
 use crate::config::Upstream;
 use crate::lambda::{Expression, Lambda};
+use crate::scalar::Scalar;
 
 /// Blueprint is an intermediary representation that allows us to generate graphQL APIs.
 /// It can only be generated from a valid Config.
@@ -108,6 +109,8 @@ pub struct EnumValueDefinition {
   pub description: Option<String>,
   pub name: String,
   pub directives: Vec<Directive>,
+  /// The values upstreams use for the enum value, when they differ from its name.
+  pub alias: Vec<async_graphql::Value>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -165,6 +168,8 @@ pub struct ScalarTypeDefinition {
   pub name: String,
   pub directive: Vec<Directive>,
   pub description: Option<String>,
+  /// The built-in scalar of the same name, unless the config declares the scalar itself.
+  pub scalar: Option<Scalar>,
 }
 
 #[derive(Clone, Debug)]
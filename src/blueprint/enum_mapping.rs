@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use async_graphql::{Name, Value};
+use indexmap::IndexMap;
+
+use super::{Blueprint, Definition, InputFieldDefinition, Type};
+
+/// Maps the values of enums between their names in the schema and the values upstreams use for them,
+/// for the enums that have values with an alias.
+#[derive(Default)]
+pub struct EnumMapping {
+  enums: HashMap<String, Vec<(Name, Vec<Value>)>>,
+  inputs: HashMap<String, Vec<InputFieldDefinition>>,
+}
+
+impl EnumMapping {
+  pub fn new(blueprint: &Blueprint) -> Self {
+    let mut mapping = EnumMapping::default();
+    for def in blueprint.definitions.iter() {
+      match def {
+        Definition::EnumTypeDefinition(def) if def.enum_values.iter().any(|value| !value.alias.is_empty()) => {
+          let values = def
+            .enum_values
+            .iter()
+            .map(|value| (Name::new(&value.name), value.alias.clone()))
+            .collect();
+          mapping.enums.insert(def.name.clone(), values);
+        }
+        Definition::InputObjectTypeDefinition(def) => {
+          mapping.inputs.insert(def.name.clone(), def.fields.clone());
+        }
+        _ => {}
+      }
+    }
+    mapping
+  }
+
+  /// Whether values of the type can hold enum values to map.
+  pub fn maps(&self, of_type: &Type) -> bool {
+    !self.enums.is_empty() && self.maps_named(of_type.name(), &mut Vec::new())
+  }
+
+  fn maps_named<'a>(&'a self, name: &'a str, seen: &mut Vec<&'a str>) -> bool {
+    if self.enums.contains_key(name) {
+      return true;
+    }
+    if seen.contains(&name) {
+      return false;
+    }
+    seen.push(name);
+    match self.inputs.get(name) {
+      Some(fields) => fields.iter().any(|field| self.maps_named(field.of_type.name(), seen)),
+      None => false,
+    }
+  }
+
+  /// Replaces the enum values in arguments by the first value upstreams use for them.
+  pub fn args_to_upstream(
+    &self,
+    args: &[InputFieldDefinition],
+    values: &IndexMap<Name, Value>,
+  ) -> IndexMap<Name, Value> {
+    values
+      .iter()
+      .map(
+        |(name, value)| match args.iter().find(|arg| arg.name == name.as_str()) {
+          Some(arg) => (name.clone(), self.to_upstream(arg.of_type.name(), value.clone())),
+          None => (name.clone(), value.clone()),
+        },
+      )
+      .collect()
+  }
+
+  fn to_upstream(&self, type_name: &str, value: Value) -> Value {
+    match value {
+      Value::List(items) => Value::List(
+        items
+          .into_iter()
+          .map(|item| self.to_upstream(type_name, item))
+          .collect(),
+      ),
+      Value::Enum(name) => self
+        .enum_to_upstream(type_name, name.as_str())
+        .unwrap_or(Value::Enum(name)),
+      Value::String(name) => self.enum_to_upstream(type_name, &name).unwrap_or(Value::String(name)),
+      Value::Object(fields) => match self.inputs.get(type_name) {
+        Some(input_fields) => Value::Object(
+          fields
+            .into_iter()
+            .map(
+              |(name, value)| match input_fields.iter().find(|field| field.name == name.as_str()) {
+                Some(field) => {
+                  let value = self.to_upstream(field.of_type.name(), value);
+                  (name, value)
+                }
+                None => (name, value),
+              },
+            )
+            .collect(),
+        ),
+        None => Value::Object(fields),
+      },
+      value => value,
+    }
+  }
+
+  fn enum_to_upstream(&self, type_name: &str, name: &str) -> Option<Value> {
+    let (_, alias) = self.enums.get(type_name)?.iter().find(|(value, _)| value == name)?;
+    alias.first().cloned()
+  }
+
+  /// Replaces the values upstreams use for enum values by their names.
+  pub fn value_from_upstream(&self, of_type: &Type, value: Value) -> Value {
+    let Some(values) = self.enums.get(of_type.name()) else {
+      return value;
+    };
+    self.enum_value_from_upstream(values, value)
+  }
+
+  fn enum_value_from_upstream(&self, values: &[(Name, Vec<Value>)], value: Value) -> Value {
+    match value {
+      Value::List(items) => Value::List(
+        items
+          .into_iter()
+          .map(|item| self.enum_value_from_upstream(values, item))
+          .collect(),
+      ),
+      value => match values.iter().find(|(_, alias)| alias.contains(&value)) {
+        Some((name, _)) => Value::Enum(name.clone()),
+        None => value,
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use async_graphql::{Name, Value};
+  use indexmap::IndexMap;
+  use pretty_assertions::assert_eq;
+
+  use super::EnumMapping;
+  use crate::blueprint::{
+    Blueprint, Definition, EnumTypeDefinition, EnumValueDefinition, InputFieldDefinition, InputObjectTypeDefinition,
+    Type,
+  };
+
+  fn named(name: &str) -> Type {
+    Type::NamedType { name: name.to_string(), non_null: false }
+  }
+
+  fn input_field(name: &str, of_type: Type) -> InputFieldDefinition {
+    InputFieldDefinition { name: name.to_string(), of_type, default_value: None, description: None }
+  }
+
+  fn mapping() -> EnumMapping {
+    let status = EnumTypeDefinition {
+      name: "Status".to_string(),
+      directives: Vec::new(),
+      description: None,
+      enum_values: vec![
+        EnumValueDefinition {
+          description: None,
+          name: "IN_PROGRESS".to_string(),
+          directives: Vec::new(),
+          alias: vec![Value::String("in_progress".to_string())],
+        },
+        EnumValueDefinition { description: None, name: "DONE".to_string(), directives: Vec::new(), alias: Vec::new() },
+      ],
+    };
+    let filter = InputObjectTypeDefinition {
+      name: "Filter".to_string(),
+      fields: vec![input_field("status", named("Status"))],
+      description: None,
+    };
+    let blueprint = Blueprint::default().definitions(vec![
+      Definition::EnumTypeDefinition(status),
+      Definition::InputObjectTypeDefinition(filter),
+    ]);
+    EnumMapping::new(&blueprint)
+  }
+
+  #[test]
+  fn test_value_from_upstream() {
+    let mapping = mapping();
+    let value = Value::List(vec![
+      Value::String("in_progress".to_string()),
+      Value::String("DONE".to_string()),
+    ]);
+    assert_eq!(
+      mapping.value_from_upstream(&named("Status"), value),
+      Value::List(vec![
+        Value::Enum(Name::new("IN_PROGRESS")),
+        Value::String("DONE".to_string())
+      ])
+    );
+  }
+
+  #[test]
+  fn test_args_to_upstream() {
+    let mapping = mapping();
+    let args = vec![
+      input_field("status", named("Status")),
+      input_field("filter", named("Filter")),
+    ];
+    assert!(mapping.maps(&named("Filter")));
+    assert!(!mapping.maps(&named("String")));
+
+    let filter = Value::Object(IndexMap::from([(
+      Name::new("status"),
+      Value::Enum(Name::new("IN_PROGRESS")),
+    )]));
+    let values = IndexMap::from([
+      (Name::new("status"), Value::Enum(Name::new("DONE"))),
+      (Name::new("filter"), filter),
+    ]);
+    let values = mapping.args_to_upstream(&args, &values);
+    assert_eq!(values[&Name::new("status")], Value::Enum(Name::new("DONE")));
+    assert_eq!(
+      values[&Name::new("filter")],
+      Value::Object(IndexMap::from([(
+        Name::new("status"),
+        Value::String("in_progress".to_string())
+      )]))
+    );
+  }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
 
 use async_graphql::parser::types::ConstDirective;
 #[allow(unused_imports)]
@@ -10,20 +11,24 @@ use hyper::header::{HeaderName, HeaderValue};
 use hyper::HeaderMap;
 use regex::Regex;
 
-use super::UnionTypeDefinition;
+use super::{rename, UnionTypeDefinition};
 use crate::blueprint::Type::ListType;
 use crate::blueprint::*;
-use crate::config::group_by::GroupBy;
-use crate::config::{Arg, Batch, Config, Field, InlineType, Upstream};
+use crate::config::group_by::{BatchEncoding, GroupBy, OnMissingKey};
+use crate::config::{Arg, Batch, Config, Encoding, ExprBody, Field, InlineType, PipeStep, Upstream};
 use crate::directive::DirectiveCodec;
 use crate::endpoint::Endpoint;
 use crate::http::Method;
 use crate::json::JsonSchema;
+use crate::json_template::JsonTemplate;
 use crate::lambda::Expression::Literal;
-use crate::lambda::{Expression, Lambda, Operation};
+use crate::lambda::{Context, Expression, Lambda, Math, Operation};
+use crate::mustache::{Filter, Mustache, Segment};
 use crate::request_template::RequestTemplate;
+use crate::scalar::Scalar;
 use crate::try_fold::TryFold;
 use crate::valid::{Valid, ValidationError};
+use crate::wasm::WasmPlugin;
 use crate::{blueprint, config};
 
 type TryFoldConfig<'a, A> = TryFold<'a, Config, A, String>;
@@ -59,12 +64,17 @@ pub fn config_blueprint<'a>() -> TryFold<'a, Config, Blueprint, String> {
 fn to_upstream<'a>() -> TryFold<'a, Config, Upstream, String> {
   TryFoldConfig::<Upstream>::new(|config, up| {
     let upstream = up.merge_right(config.upstream.clone());
-    if let Some(ref base_url) = upstream.base_url {
+    let scripts = Valid::<(), String>::fail("scripts require the unsafe-js feature".to_string())
+      .trace("scripts")
+      .trace("@upstream")
+      .when(|| upstream.scripts.is_some() && !cfg!(feature = "unsafe-js"));
+    let base_url = if let Some(ref base_url) = upstream.base_url {
       Valid::from(reqwest::Url::parse(base_url).map_err(|e| ValidationError::new(e.to_string())))
         .map_to(upstream.clone())
     } else {
       Valid::succeed(upstream.clone())
-    }
+    };
+    scripts.and(base_url)
   })
 }
 
@@ -72,7 +82,7 @@ pub fn apply_batching(mut blueprint: Blueprint) -> Blueprint {
   for def in blueprint.definitions.iter() {
     if let Definition::ObjectTypeDefinition(object_type_definition) = def {
       for field in object_type_definition.fields.iter() {
-        if let Some(Expression::Unsafe(Operation::Endpoint(_request_template, Some(_), _dl))) = field.resolver.clone() {
+        if field.resolver.as_ref().is_some_and(is_batched) {
           blueprint.upstream.batch = blueprint.upstream.batch.or(Some(Batch::default()));
           return blueprint;
         }
@@ -82,6 +92,15 @@ pub fn apply_batching(mut blueprint: Blueprint) -> Blueprint {
   blueprint
 }
 
+fn is_batched(expression: &Expression) -> bool {
+  match expression {
+    Expression::Unsafe(Operation::Endpoint(_request_template, Some(_), _dl)) => true,
+    Expression::Pipe(steps) => steps.iter().any(is_batched),
+    Expression::Transform(expr, _) => is_batched(expr),
+    _ => false,
+  }
+}
+
 fn to_directive(const_directive: ConstDirective) -> Valid<Directive, String> {
   const_directive
     .arguments
@@ -120,16 +139,18 @@ fn to_definitions<'a>() -> TryFold<'a, Config, Vec<Definition>, String> {
   TryFold::<Config, Vec<Definition>, String>::new(|config, _| {
     let output_types = config.output_types();
     let input_types = config.input_types();
+    let renames = type_renames(config);
     Valid::from_iter(config.graphql.types.iter(), |(name, type_)| {
       let dbl_usage = input_types.contains(name) && output_types.contains(name);
-      if let Some(variants) = &type_.variants {
+      let modify = validate_type_modify(name, type_, config).trace("@modify").trace(name);
+      let definition = if let Some(variants) = &type_.variants {
         if !variants.is_empty() {
           to_enum_type_definition(name, type_, config, variants.clone()).trace(name)
         } else {
           Valid::fail("No variants found for enum".to_string())
         }
       } else if type_.scalar {
-        to_scalar_type_definition(name).trace(name)
+        to_scalar_type_definition(name, None).trace(name)
       } else if dbl_usage {
         Valid::fail("type is used in input and output".to_string()).trace(name)
       } else {
@@ -147,9 +168,17 @@ fn to_definitions<'a>() -> TryFold<'a, Config, Vec<Definition>, String> {
             }
             _ => Valid::succeed(definition),
           })
-      }
+      };
+      modify.and(definition)
     })
     .map(|mut types| {
+      // Built-in scalars that aren't declared, the unused ones are removed by `compress`
+      types.extend(
+        Scalar::ALL
+          .iter()
+          .filter(|scalar| !config.graphql.types.contains_key(scalar.name()))
+          .filter_map(|scalar| to_scalar_type_definition(scalar.name(), Some(*scalar)).to_result().ok()),
+      );
       types.extend(
         config
           .graphql
@@ -158,16 +187,51 @@ fn to_definitions<'a>() -> TryFold<'a, Config, Vec<Definition>, String> {
           .map(to_union_type_definition)
           .map(Definition::UnionTypeDefinition),
       );
-      types
+      rename::rename_types(types, &renames)
     })
   })
 }
 
-fn to_scalar_type_definition(name: &str) -> Valid<Definition, String> {
+/// The names of the types renamed with `@modify(name:)`, by their name in the config.
+fn type_renames(config: &Config) -> HashMap<String, String> {
+  config
+    .graphql
+    .types
+    .iter()
+    .filter_map(|(name, type_)| Some((name.clone(), type_.modify.as_ref()?.name.clone()?)))
+    .collect()
+}
+
+fn validate_type_modify(name: &str, type_: &config::Type, config: &Config) -> Valid<(), String> {
+  let Some(modify) = &type_.modify else {
+    return Valid::succeed(());
+  };
+  let omit = Valid::<(), String>::fail("types can't be omitted".to_string()).when(|| modify.omit);
+  let Some(new_name) = &modify.name else {
+    return omit;
+  };
+  let schema = &config.graphql.schema;
+  let is_root = schema.query.as_deref() == Some(name) || schema.mutation.as_deref() == Some(name);
+  let exists = config.graphql.unions.contains_key(new_name)
+    || config.graphql.types.iter().any(|(other, other_type)| {
+      let other_name = other_type
+        .modify
+        .as_ref()
+        .and_then(|m| m.name.as_ref())
+        .unwrap_or(other);
+      other != name && other_name == new_name
+    });
+  omit
+    .and(Valid::<(), String>::fail("root types can't be renamed".to_string()).when(|| is_root))
+    .and(Valid::<(), String>::fail(format!("type '{new_name}' already exists")).when(|| exists))
+}
+
+fn to_scalar_type_definition(name: &str, scalar: Option<Scalar>) -> Valid<Definition, String> {
   Valid::succeed(Definition::ScalarTypeDefinition(ScalarTypeDefinition {
     name: name.to_string(),
     directive: Vec::new(),
-    description: None,
+    description: scalar.map(|scalar| scalar.description().to_string()),
+    scalar,
   }))
 }
 fn to_union_type_definition((name, u): (&String, &config::Union)) -> UnionTypeDefinition {
@@ -184,16 +248,61 @@ fn to_enum_type_definition(
   _config: &Config,
   variants: BTreeSet<String>,
 ) -> Valid<Definition, String> {
-  let enum_type_definition = Definition::EnumTypeDefinition(EnumTypeDefinition {
-    name: name.to_string(),
-    directives: Vec::new(),
-    description: type_.doc.clone(),
-    enum_values: variants
-      .iter()
-      .map(|variant| EnumValueDefinition { description: None, name: variant.clone(), directives: Vec::new() })
-      .collect(),
-  });
-  Valid::succeed(enum_type_definition)
+  let enum_values = variants
+    .iter()
+    .filter(|variant| !type_.variant_modify.get(*variant).is_some_and(|modify| modify.omit))
+    .map(|variant| {
+      let modify = type_.variant_modify.get(variant);
+      let new_name = modify.and_then(|modify| modify.name.clone());
+      let alias = match type_.variant_alias.get(variant) {
+        Some(alias) => to_alias_options(&alias.options).trace("@alias").trace(variant),
+        None => Valid::succeed(Vec::new()),
+      };
+      alias.map(|mut alias| {
+        // A renamed value is still read from its name in the config
+        if new_name.is_some() {
+          alias.push(ConstValue::String(variant.clone()));
+        }
+        EnumValueDefinition {
+          description: None,
+          name: new_name.unwrap_or_else(|| variant.clone()),
+          directives: Vec::new(),
+          alias,
+        }
+      })
+    });
+  Valid::from_iter(enum_values, |value| value).and_then(|enum_values| {
+    let names = enum_values.iter().map(|value| value.name.as_str());
+    let aliases = enum_values.iter().flat_map(|value| value.alias.iter());
+    Valid::from_iter(names.clone().enumerate(), |(i, name)| {
+      Valid::<(), String>::fail(format!("value '{name}' already exists"))
+        .when(|| names.clone().take(i).any(|other| other == name))
+    })
+    .and(Valid::from_iter(aliases.clone().enumerate(), |(i, alias)| {
+      Valid::<(), String>::fail(format!("alias {alias} is used by several values"))
+        .when(|| aliases.clone().take(i).any(|other| other == alias))
+    }))
+    .map_to(Definition::EnumTypeDefinition(EnumTypeDefinition {
+      name: name.to_string(),
+      directives: Vec::new(),
+      description: type_.doc.clone(),
+      enum_values,
+    }))
+  })
+}
+
+fn to_alias_options(options: &[serde_json::Value]) -> Valid<Vec<ConstValue>, String> {
+  Valid::from_iter(options.iter(), |option| match option {
+    serde_json::Value::String(_) | serde_json::Value::Number(_) => {
+      Valid::from(ConstValue::from_json(option.clone()).map_err(|e| ValidationError::new(e.to_string())))
+    }
+    option => Valid::fail(format!("option {option} is not a string or a number")),
+  })
+  .and_then(|options| {
+    Valid::<(), String>::fail("options can't be empty".to_string())
+      .when(|| options.is_empty())
+      .map_to(options)
+  })
 }
 fn to_object_type_definition(name: &str, type_of: &config::Type, config: &Config) -> Valid<Definition, String> {
   to_fields(type_of, config).map(|fields| {
@@ -229,17 +338,70 @@ fn to_interface_type_definition(definition: ObjectTypeDefinition) -> Valid<Defin
   }))
 }
 fn to_fields(type_of: &config::Type, config: &Config) -> Valid<Vec<blueprint::FieldDefinition>, String> {
-  Valid::from_iter(
-    type_of
-      .fields
-      .iter()
-      .filter(|field| field.1.modify.as_ref().map(|m| !m.omit).unwrap_or(true)),
+  let omitted = type_of
+    .omit
+    .as_ref()
+    .map(|omit| omit.fields.clone())
+    .unwrap_or_default();
+  let is_visible =
+    |name: &String, field: &Field| !omitted.contains(name) && field.modify.as_ref().map(|m| !m.omit).unwrap_or(true);
+  let omit = Valid::from_iter(omitted.iter(), |name| {
+    Valid::<(), String>::fail(format!("no field '{name}' found")).when(|| !type_of.fields.contains_key(name))
+  })
+  .trace("@omit");
+  let fields = Valid::from_iter(
+    type_of.fields.iter().filter(|(name, field)| is_visible(name, field)),
     |(name, field)| {
       validate_field_type_exist(config, field)
         .and(to_field(type_of, config, name, field))
         .trace(name)
     },
-  )
+  );
+  let added_fields = Valid::from_iter(type_of.added_fields.iter(), |added_field| {
+    let exists = type_of
+      .fields
+      .iter()
+      .any(|(name, field)| is_visible(name, field) && field_name(name, field) == added_field.name);
+    Valid::<(), String>::fail(format!("field '{}' already exists", added_field.name))
+      .when(|| exists)
+      .and(to_added_field(type_of, config, added_field))
+      .trace("@addField")
+      .trace(&added_field.name)
+  });
+  omit.and(fields).zip(added_fields).map(|(mut fields, added_fields)| {
+    fields.extend(added_fields);
+    fields
+  })
+}
+
+/// The name of a field in the schema.
+fn field_name<'a>(name: &'a str, field: &'a Field) -> &'a str {
+  field.modify.as_ref().and_then(|m| m.name.as_deref()).unwrap_or(name)
+}
+
+/// Resolves an added field like the field its path starts from, inlined to the rest of the path.
+fn to_added_field(
+  type_of: &config::Type,
+  config: &Config,
+  added_field: &config::AddField,
+) -> Valid<blueprint::FieldDefinition, String> {
+  let Some((head, tail)) = added_field.path.split_first() else {
+    return Valid::fail("path can't be empty".to_string());
+  };
+  let Some(source) = type_of.fields.get(head) else {
+    return Valid::fail(format!("no field '{head}' found"));
+  };
+  let field =
+    Field { modify: None, inline: (!tail.is_empty()).then(|| InlineType { path: tail.to_vec() }), ..source.clone() };
+  to_field(type_of, config, head, &field).map(|b_field| {
+    // Without a resolver, the value is read from the object the source field is read from
+    let b_field = if source.has_resolver() || source.transform.is_some() {
+      b_field
+    } else {
+      b_field.resolver(Some(Lambda::context_path(added_field.path.clone()).expression))
+    };
+    b_field.name(added_field.name.clone())
+  })
 }
 
 fn get_value_type(type_of: &config::Type, value: &str) -> Option<Type> {
@@ -258,8 +420,9 @@ fn get_value_type(type_of: &config::Type, value: &str) -> Option<Type> {
 fn validate_mustache_parts(
   type_of: &config::Type,
   config: &Config,
-  is_query: bool,
+  allows_null: bool,
   parts: &[String],
+  filters: &[Filter],
   args: &[InputFieldDefinition],
 ) -> Valid<(), String> {
   if parts.len() < 2 {
@@ -268,16 +431,21 @@ fn validate_mustache_parts(
 
   let head = parts[0].as_str();
   let tail = parts[1].as_str();
+  // A default makes up for a missing value, and lists and objects have to be turned into a string
+  let has_default = filters.iter().any(|filter| matches!(filter, Filter::Default(_)));
+  let takes_list = filters
+    .iter()
+    .any(|filter| matches!(filter, Filter::Join(_) | Filter::Json));
+  let takes_object = filters.iter().any(|filter| matches!(filter, Filter::Json));
 
   match head {
     "value" => {
       if let Some(val_type) = get_value_type(type_of, tail) {
-        if !is_scalar(val_type.name()) {
+        if !takes_object && !is_scalar(val_type.name()) {
           return Valid::fail(format!("value '{tail}' is not of a scalar type"));
         }
 
-        // Queries can use optional values
-        if !is_query && val_type.is_nullable() {
+        if !allows_null && !has_default && val_type.is_nullable() {
           return Valid::fail(format!("value '{tail}' is a nullable type"));
         }
       } else {
@@ -289,13 +457,13 @@ fn validate_mustache_parts(
       // constructing a HashMap since we'd have 3-4 arguments at max in
       // most cases
       if let Some(arg) = args.iter().find(|arg| arg.name == tail) {
-        if let Type::ListType { .. } = arg.of_type {
+        if !takes_list && matches!(arg.of_type, Type::ListType { .. }) {
           return Valid::fail(format!("can't use list type '{tail}' here"));
         }
 
         // we can use non-scalar types in args
 
-        if !is_query && arg.default_value.is_none() && arg.of_type.is_nullable() {
+        if !allows_null && !has_default && arg.default_value.is_none() && arg.of_type.is_nullable() {
           return Valid::fail(format!("argument '{tail}' is a nullable type"));
         }
       } else {
@@ -311,6 +479,25 @@ fn validate_mustache_parts(
       // "headers" refers to the header values known at runtime, which we can't
       // validate here
     }
+    "env" => {
+      if !config.server.env.contains(tail) {
+        return Valid::fail(format!("env '{tail}' is not allowed in the server config"));
+      }
+    }
+    "request" => {
+      if !["method", "path", "ip"].contains(&tail) {
+        return Valid::fail(format!("no request field '{tail}' found"));
+      }
+    }
+    "operation" => {
+      if tail != "name" {
+        return Valid::fail(format!("no operation field '{tail}' found"));
+      }
+    }
+    "parent" => {
+      // a type can be resolved from fields of different types, so the
+      // parent can't be validated here
+    }
     _ => {
       return Valid::fail(format!("unknown template directive '{head}'"));
     }
@@ -327,14 +514,15 @@ fn validate_field(type_of: &config::Type, config: &Config, field: &FieldDefiniti
   // context from that method alone
   // So we must duplicate some of that logic here :(
   if let Some(Expression::Unsafe(Operation::Endpoint(req_template, _, _))) = &field.resolver {
-    Valid::from_iter(req_template.root_url.expression_segments(), |parts| {
-      validate_mustache_parts(type_of, config, false, parts, &field.args).trace("path")
+    Valid::from_iter(req_template.root_url.expressions(), |(parts, filters)| {
+      validate_mustache_parts(type_of, config, false, parts, filters, &field.args).trace("path")
     })
     .and(Valid::from_iter(req_template.query.clone(), |query| {
       let (_, mustache) = query;
 
-      Valid::from_iter(mustache.expression_segments(), |parts| {
-        validate_mustache_parts(type_of, config, true, parts, &field.args).trace("query")
+      Valid::from_iter(mustache.expressions(), |(parts, filters)| {
+        // A query param that renders empty is dropped, so a missing value needs an explicit default
+        validate_mustache_parts(type_of, config, false, parts, filters, &field.args).trace("query")
       })
     }))
     .unit()
@@ -358,6 +546,12 @@ fn to_field(
     .and(update_http().trace("@http"))
     .and(update_unsafe().trace("@unsafe"))
     .and(update_const_field().trace("@const"))
+    .and(update_expr().trace("@expr"))
+    .and(update_pipe().trace("@pipe"))
+    .and(update_call().trace("@call"))
+    .and(update_wasm().trace("@wasm"))
+    .and(update_rust().trace("@rust"))
+    .and(update_transform().trace("@transform"))
     .and(update_inline_field().trace("@inline"))
     .and(update_modify().trace("@modify"))
     .try_fold(&(config, field, type_of, name), FieldDefinition::default())
@@ -429,69 +623,190 @@ fn update_unsafe<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type,
   })
 }
 
+fn update_wasm<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(|(_, field, _, _), b_field| {
+    match &field.wasm {
+      Some(wasm) => Valid::from(
+        std::fs::read(&wasm.module)
+          .map_err(|e| ValidationError::new(format!("Unable to read module: {}", e)))
+          .and_then(|bytes| {
+            WasmPlugin::new(&bytes, &wasm.function, wasm.get_limits()).map_err(|e| ValidationError::new(e.to_string()))
+          }),
+      )
+      .trace(&wasm.module)
+      .map(|plugin| {
+        let plugin = Arc::new(plugin);
+        b_field.resolver_or_default(Lambda::context().to_wasm(plugin.clone()), |r| r.to_wasm(plugin.clone()))
+      }),
+      None => Valid::succeed(b_field),
+    }
+  })
+}
+
+fn update_rust<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(|(_, field, _, _), b_field| {
+    match &field.rust {
+      Some(rust) => {
+        Valid::succeed(b_field.resolver(Some(Expression::Unsafe(Operation::Rust(rust.name.clone(), None)))))
+      }
+      None => Valid::succeed(b_field),
+    }
+  })
+}
+
 fn update_http<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
   TryFold::<(&Config, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
     |(config, field, type_of, _), b_field| match field.http.as_ref() {
-      Some(http) => match http
-        .base_url
-        .as_ref()
-        .map_or_else(|| config.upstream.base_url.as_ref(), Some)
-      {
-        Some(base_url) => {
-          let mut base_url = base_url.clone();
-          if base_url.ends_with('/') {
-            base_url.pop();
-          }
-          base_url.push_str(http.path.clone().as_str());
-          let query = http.query.clone().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
-          let output_schema = to_json_schema_for_field(field, config);
-          let input_schema = to_json_schema_for_args(&field.args, config);
-
-          Valid::<(), String>::fail("GroupBy is only supported for GET requests".to_string())
-            .when(|| !http.group_by.is_empty() && http.method != Method::GET)
-            .and(Valid::from_iter(http.headers.iter(), |(k, v)| {
-              let name =
-                Valid::from(HeaderName::from_bytes(k.as_bytes()).map_err(|e| ValidationError::new(e.to_string())));
-
-              let value =
-                Valid::from(HeaderValue::from_str(v.as_str()).map_err(|e| ValidationError::new(e.to_string())));
-
-              name.zip(value).map(|(name, value)| (name, value))
-            }))
-            .map(HeaderMap::from_iter)
-            .and_then(|header_map| {
-              RequestTemplate::try_from(
-                Endpoint::new(base_url.to_string())
-                  .method(http.method.clone())
-                  .query(query)
-                  .output(output_schema)
-                  .input(input_schema)
-                  .body(http.body.clone())
-                  .headers(header_map),
-              )
-              .map_err(|e| ValidationError::new(e.to_string()))
-              .into()
-            })
-            .map(|req_template| {
-              if !http.group_by.is_empty() && http.method == Method::GET {
-                b_field.resolver(Some(Expression::Unsafe(Operation::Endpoint(
-                  req_template,
-                  Some(GroupBy::new(http.group_by.clone())),
-                  None,
-                ))))
-              } else {
-                b_field.resolver(Some(Lambda::from_request_template(req_template).expression))
-              }
-            })
-            .and_then(|b_field| validate_field(type_of, config, &b_field).map_to(b_field))
-        }
-        None => Valid::fail("No base URL defined".to_string()),
-      },
+      Some(http) => to_http_expression(config, field, http, to_output_schema(field, config))
+        .map(|resolver| b_field.resolver(Some(resolver)))
+        .and_then(|b_field| validate_field(type_of, config, &b_field).map_to(b_field)),
       None => Valid::succeed(b_field),
     },
   )
 }
 
+fn to_http_expression(
+  config: &Config,
+  field: &Field,
+  http: &config::Http,
+  output_schema: JsonSchema,
+) -> Valid<Expression, String> {
+  match http
+    .base_url
+    .as_ref()
+    .map_or_else(|| config.upstream.base_url.as_ref(), Some)
+  {
+    Some(base_url) => {
+      let mut base_url = base_url.clone();
+      if base_url.ends_with('/') {
+        base_url.pop();
+      }
+      base_url.push_str(http.path.clone().as_str());
+      let query = http.query.clone().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+      let input_schema = to_json_schema_for_args(&field.args, config);
+
+      Valid::<(), String>::fail(
+        "GroupBy is only supported for GET requests, or POST requests with a batchKey".to_string(),
+      )
+      .when(|| !http.group_by.is_empty() && http.method != Method::GET && http.batch_key.is_none())
+      .and(
+        Valid::<(), String>::fail("batchKey is only supported for POST requests with groupBy".to_string())
+          .when(|| http.batch_key.is_some() && (http.group_by.is_empty() || http.method != Method::POST)),
+      )
+      .and(
+        Valid::<(), String>::fail("GroupBy keys must have distinct query params".to_string()).when(|| {
+          let keys = http.group_by.to_keys();
+          let query_params = keys.iter().map(|key| key.query_param()).collect::<BTreeSet<_>>();
+          query_params.len() != keys.len()
+        }),
+      )
+      .and(
+        Valid::<(), String>::fail("batchEncoding is only supported for GET requests with groupBy".to_string()).when(
+          || http.batch_encoding != BatchEncoding::Repeat && (http.group_by.is_empty() || http.method != Method::GET),
+        ),
+      )
+      .and(
+        Valid::<(), String>::fail("PATH batchEncoding requires exactly one groupBy key".to_string())
+          .when(|| http.batch_encoding == BatchEncoding::Path && http.group_by.to_keys().len() != 1),
+      )
+      .and(
+        Valid::<(), String>::fail("onMissingKey is only supported with groupBy".to_string())
+          .when(|| http.on_missing_key != OnMissingKey::Null && http.group_by.is_empty()),
+      )
+      .and(
+        Valid::<(), String>::fail("itemPath is only supported with groupBy".to_string())
+          .when(|| !http.item_path.is_empty() && http.group_by.is_empty()),
+      )
+      .and(
+        Valid::<(), String>::fail("itemPath must be a parent of every groupBy path".to_string()).when(|| {
+          !http.item_path.is_empty()
+            && http
+              .group_by
+              .to_keys()
+              .iter()
+              .any(|key| key.path().len() <= http.item_path.len() || !key.path().starts_with(&http.item_path))
+        }),
+      )
+      .and(
+        Valid::<(), String>::fail("FORM_URL_ENCODED and MULTIPART bodies must be an object or a template".to_string())
+          .when(|| {
+            matches!(http.encoding, Encoding::FormUrlEncoded | Encoding::Multipart)
+              && http
+                .body
+                .as_ref()
+                .is_some_and(|body| !body.is_object() && !body.is_string())
+          })
+          .trace("body"),
+      )
+      .and(validate_templates(http))
+      .and(Valid::from_iter(http.headers.iter(), |(k, v)| {
+        let name = Valid::from(HeaderName::from_bytes(k.as_bytes()).map_err(|e| ValidationError::new(e.to_string())));
+
+        let value = Valid::from(HeaderValue::from_str(v.as_str()).map_err(|e| ValidationError::new(e.to_string())));
+
+        name.zip(value).map(|(name, value)| (name, value))
+      }))
+      .map(HeaderMap::from_iter)
+      .and_then(|header_map| {
+        RequestTemplate::try_from(
+          Endpoint::new(base_url.to_string())
+            .method(http.method.clone())
+            .query(query)
+            .output(output_schema)
+            .input(input_schema)
+            .body(http.body.clone())
+            .encoding(http.encoding.clone())
+            .response_format(http.response_format.clone())
+            .headers(header_map),
+        )
+        .map_err(|e| ValidationError::new(e.to_string()))
+        .into()
+      })
+      .map(|req_template| {
+        let upload_args = field
+          .args
+          .iter()
+          .filter(|(_, arg)| arg.type_of == "Upload")
+          .map(|(name, _)| name.clone())
+          .collect();
+        let req_template = req_template.upload_args(upload_args);
+        if !http.group_by.is_empty() {
+          Expression::Unsafe(Operation::Endpoint(
+            req_template,
+            Some(
+              GroupBy::from_keys(http.group_by.to_keys())
+                .with_batch_key(http.batch_key.clone())
+                .with_encoding(http.batch_encoding.clone())
+                .with_on_missing_key(http.on_missing_key.clone())
+                .with_item_path(Some(http.item_path.clone()).filter(|path| !path.is_empty())),
+            ),
+            None,
+          ))
+        } else {
+          Lambda::from_request_template(req_template).expression
+        }
+      })
+    }
+    None => Valid::fail("No base URL defined".to_string()),
+  }
+}
+
+/// Checks that every template of an `@http` directive parses.
+fn validate_templates(http: &config::Http) -> Valid<(), String> {
+  let parse = |template: &str| Valid::from(Mustache::parse(template).map_err(|e| ValidationError::new(e.to_string())));
+  parse(http.path.as_str())
+    .trace("path")
+    .and(Valid::from_iter(http.query.iter(), |(k, v)| parse(v).trace(k)).trace("query"))
+    .and(Valid::from_iter(http.headers.iter(), |(k, v)| parse(v).trace(k)).trace("headers"))
+    .and(
+      Valid::from_iter(http.body.iter(), |body| {
+        Valid::from(JsonTemplate::parse(body).map_err(|e| ValidationError::new(e.to_string())))
+      })
+      .trace("body"),
+    )
+    .unit()
+}
+
 fn update_modify<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
   TryFold::<(&Config, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
     |(config, field, type_of, _), mut b_field| {
@@ -515,6 +830,32 @@ fn update_modify<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type,
     },
   )
 }
+fn update_transform<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(|(_, field, _, _), b_field| {
+    match field.transform.as_ref() {
+      Some(transform) => {
+        let names = transform.rename.values().collect::<BTreeSet<_>>();
+        Valid::<(), String>::fail("wrap can't be empty".to_string())
+          .when(|| transform.wrap.as_ref().is_some_and(String::is_empty))
+          .and(
+            Valid::<(), String>::fail("rename can't map several keys to the same name".to_string())
+              .when(|| names.len() != transform.rename.len()),
+          )
+          .map(|_| {
+            let lambda = Lambda::context_field(b_field.name.clone());
+            let b_field = b_field.resolver_or_default(lambda, |r| r);
+            let resolver = b_field
+              .resolver
+              .clone()
+              .map(|resolver| Expression::Transform(Box::new(resolver), transform.clone()));
+            b_field.resolver(resolver)
+          })
+      }
+      None => Valid::succeed(b_field),
+    }
+  })
+}
+
 fn update_const_field<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
 {
   TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(|(config, field, _, _), b_field| {
@@ -522,8 +863,9 @@ fn update_const_field<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::T
     match field.const_field.as_ref() {
       Some(const_field) => {
         let data = const_field.data.to_owned();
+        let schema = to_output_schema(field, config);
         match ConstValue::from_json(data.to_owned()) {
-          Ok(gql_value) => match to_json_schema_for_field(field, config).validate(&gql_value).to_result() {
+          Ok(gql_value) => match schema.validate(&gql_value).to_result() {
             Ok(_) => {
               updated_b_field.resolver = Some(Literal(data));
               Valid::succeed(updated_b_field)
@@ -537,8 +879,317 @@ fn update_const_field<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::T
     }
   })
 }
+fn update_expr<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(|(config, field, _, _), b_field| {
+    match field.expr.as_ref() {
+      Some(expr) => to_expression(&expr.body, field, config).map(|expression| b_field.resolver(Some(expression))),
+      None => Valid::succeed(b_field),
+    }
+  })
+}
+
+fn update_pipe<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(
+    |(config, field, type_of, _), b_field| match field.pipe.as_ref() {
+      Some(pipe) => Valid::<(), String>::fail("steps can't be empty".to_string())
+        .when(|| pipe.steps.is_empty())
+        .and(Valid::from_iter(pipe.steps.iter().enumerate(), |(i, step)| {
+          // Only the first step sees the parent value and only the last one produces the field's value
+          let is_last = i + 1 == pipe.steps.len();
+          to_pipe_step_expression(config, field, step, is_last)
+            .and_then(|expression| {
+              let step_field = b_field.clone().resolver(Some(expression.clone()));
+              let validation = if i == 0 {
+                validate_field(type_of, config, &step_field)
+              } else {
+                Valid::succeed(())
+              };
+              validation.map_to(expression)
+            })
+            .trace(i.to_string().as_str())
+        }))
+        .map(|steps| b_field.resolver(Some(Expression::Pipe(steps)))),
+      None => Valid::succeed(b_field),
+    },
+  )
+}
+
+fn update_call<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'a str), FieldDefinition, String> {
+  TryFold::<(&Config, &Field, &config::Type, &str), FieldDefinition, String>::new(
+    |(config, field, type_of, _), b_field| match field.call.as_ref() {
+      Some(call) => to_call_target(config, call).and_then(|(target_type, name, target)| {
+        let args = match to_call_args(call, target).to_result() {
+          Ok(args) => args,
+          Err(e) => return Valid::from_validation_err(e),
+        };
+        Valid::from_iter(call.args.keys(), |arg| {
+          Valid::<(), String>::fail(format!("no argument '{arg}' found")).when(|| !target.args.contains_key(arg))
+        })
+        .and(Valid::from_iter(target.args.iter(), |(arg, target_arg)| {
+          Valid::<(), String>::fail(format!("argument '{arg}' is required"))
+            .when(|| target_arg.required && target_arg.default_value.is_none() && !call.args.contains_key(arg))
+        }))
+        .and(Valid::from_iter(args.iter(), |(arg, (_, mustache))| {
+          // Optional values can be passed on as they are
+          Valid::from_iter(mustache.expressions(), |(parts, filters)| {
+            validate_mustache_parts(type_of, config, true, parts, filters, &b_field.args)
+          })
+          .trace(arg)
+        }))
+        .and(Valid::<(), String>::fail("nested @call is not supported".to_string()).when(|| target.call.is_some()))
+        .and(
+          to_field(target_type, config, name, target)
+            .and_then(|target| Valid::from_option(target.resolver, format!("field '{name}' has no resolver"))),
+        )
+        .and_then(|resolver| match substitute_args(resolver, &args) {
+          Ok(resolver) => Valid::succeed(b_field.resolver(Some(resolver))),
+          Err(e) => Valid::fail(e),
+        })
+      }),
+      None => Valid::succeed(b_field),
+    },
+  )
+}
+
+fn to_call_target<'a>(
+  config: &'a Config,
+  call: &'a config::Call,
+) -> Valid<(&'a config::Type, &'a str, &'a Field), String> {
+  let (root, name) = match (&call.query, &call.mutation) {
+    (Some(name), None) => (&config.graphql.schema.query, name),
+    (None, Some(name)) => (&config.graphql.schema.mutation, name),
+    _ => return Valid::fail("either query or mutation must be specified".to_string()),
+  };
+  let root = root.as_deref().unwrap_or_default();
+  match config
+    .find_type(root)
+    .and_then(|type_of| Some((type_of, type_of.fields.get(name)?)))
+  {
+    Some((type_of, field)) => Valid::succeed((type_of, name.as_str(), field)),
+    None => Valid::fail(format!("field '{name}' not found in type '{root}'")),
+  }
+}
+
+/// The arguments the target field gets called with, along with the defaults of the ones that aren't passed.
+fn to_call_args(call: &config::Call, target: &Field) -> Valid<BTreeMap<String, (serde_json::Value, Mustache)>, String> {
+  let defaults = target
+    .args
+    .iter()
+    .filter(|(name, _)| !call.args.contains_key(*name))
+    .filter_map(|(name, arg)| Some((name, arg.default_value.as_ref()?)));
+  Valid::from_iter(defaults.chain(call.args.iter()), |(name, value)| {
+    let mustache = match value {
+      serde_json::Value::String(s) => Valid::from(Mustache::parse(s).map_err(|e| ValidationError::new(e.to_string()))),
+      value => Valid::succeed(Mustache::from(vec![Segment::Literal(value.to_string())])),
+    };
+    mustache
+      .map(|mustache| (name.clone(), (value.clone(), mustache)))
+      .trace(name)
+  })
+  .map(BTreeMap::from_iter)
+}
+
+/// Inlines the arguments of a call into the target field's expression.
+fn substitute_args(
+  expression: Expression,
+  args: &BTreeMap<String, (serde_json::Value, Mustache)>,
+) -> Result<Expression, String> {
+  let substitute = |expression: Expression| substitute_args(expression, args);
+  let substitute_box = |expression: Box<Expression>| substitute(*expression).map(Box::new);
+  let substitute_vec =
+    |expressions: Vec<Expression>| expressions.into_iter().map(substitute).collect::<Result<Vec<_>, _>>();
+  let substitute_mustache = |mustache: &Mustache| {
+    mustache.try_substitute(&|parts: &[String]| match parts {
+      [head, name, rest @ ..] if head == "args" => match (args.get(name).map(|(_, m)| m.segments()), rest) {
+        (None, _) => Ok(Some(vec![])),
+        (Some(segments), []) => Ok(Some(segments.to_vec())),
+        (Some([Segment::Expression(path)]), rest) => Ok(Some(vec![Segment::Expression(
+          path.iter().chain(rest).cloned().collect(),
+        )])),
+        _ => Err(format!("can't access '{}' of argument '{name}'", parts.join("."))),
+      },
+      _ => Ok(None),
+    })
+  };
+
+  Ok(match expression {
+    Expression::Context(Context::Args(path)) => match path.split_first() {
+      Some((name, rest)) => match args.get(name) {
+        None => Expression::Literal(serde_json::Value::Null),
+        Some((value, _)) if !value.is_string() && rest.is_empty() => Expression::Literal(value.clone()),
+        Some((_, mustache)) if mustache.segments().iter().any(|s| matches!(s, Segment::Filtered(_, _))) => {
+          return Err(format!(
+            "can't apply filters to argument '{name}' outside of a template"
+          ))
+        }
+        Some((_, mustache)) => {
+          let to_expression = |segment: &Segment, rest: &[String]| match segment {
+            Segment::Literal(text) => Expression::Literal(serde_json::Value::String(text.clone())),
+            Segment::Expression(parts) | Segment::Filtered(parts, _) => {
+              let path = parts.iter().skip(1).chain(rest).cloned().collect::<Vec<_>>();
+              Expression::Context(match parts[0].as_str() {
+                "value" if path.is_empty() => Context::Value,
+                "value" => Context::Path(path),
+                "args" => Context::Args(path),
+                "vars" => Context::Var(path.join(".")),
+                "headers" => Context::Header(path.join(".")),
+                _ => Context::Template(parts.iter().chain(rest).cloned().collect()),
+              })
+            }
+          };
+          match (mustache.segments(), rest) {
+            ([segment], rest) if matches!(segment, Segment::Expression(_)) || rest.is_empty() => {
+              to_expression(segment, rest)
+            }
+            (segments, []) => Expression::Concat(segments.iter().map(|segment| to_expression(segment, &[])).collect()),
+            _ => return Err(format!("can't access 'args.{}' of argument '{name}'", path.join("."))),
+          }
+        }
+      },
+      None => Expression::Context(Context::Args(path)),
+    },
+    Expression::Unsafe(Operation::Endpoint(mut req_template, group_by, dl)) => {
+      req_template.root_url = substitute_mustache(&req_template.root_url)?;
+      req_template.query = req_template
+        .query
+        .iter()
+        .map(|(k, v)| Ok((k.clone(), substitute_mustache(v)?)))
+        .collect::<Result<_, String>>()?;
+      req_template.headers = req_template
+        .headers
+        .iter()
+        .map(|(k, v)| Ok((k.clone(), substitute_mustache(v)?)))
+        .collect::<Result<_, String>>()?;
+      req_template.body = req_template
+        .body
+        .as_ref()
+        .map(|body| body.try_map(&substitute_mustache))
+        .transpose()?;
+      Expression::Unsafe(Operation::Endpoint(req_template, group_by, dl))
+    }
+    Expression::Unsafe(Operation::JS(input, script)) => {
+      Expression::Unsafe(Operation::JS(substitute_box(input)?, script))
+    }
+    Expression::Unsafe(Operation::Wasm(input, plugin)) => {
+      Expression::Unsafe(Operation::Wasm(substitute_box(input)?, plugin))
+    }
+    Expression::Unsafe(Operation::Rust(name, _)) => return Err(format!("the @rust resolver '{name}' can't be called")),
+    Expression::Input(input, path) => Expression::Input(substitute_box(input)?, path),
+    Expression::EqualTo(l, r) => Expression::EqualTo(substitute_box(l)?, substitute_box(r)?),
+    Expression::If { cond, then, els } => {
+      Expression::If { cond: substitute_box(cond)?, then: substitute_box(then)?, els: substitute_box(els)? }
+    }
+    Expression::And(exprs) => Expression::And(substitute_vec(exprs)?),
+    Expression::Or(exprs) => Expression::Or(substitute_vec(exprs)?),
+    Expression::Not(expr) => Expression::Not(substitute_box(expr)?),
+    Expression::Gt(l, r) => Expression::Gt(substitute_box(l)?, substitute_box(r)?),
+    Expression::Gte(l, r) => Expression::Gte(substitute_box(l)?, substitute_box(r)?),
+    Expression::Lt(l, r) => Expression::Lt(substitute_box(l)?, substitute_box(r)?),
+    Expression::Lte(l, r) => Expression::Lte(substitute_box(l)?, substitute_box(r)?),
+    Expression::Contains(l, r) => Expression::Contains(substitute_box(l)?, substitute_box(r)?),
+    Expression::IsNull(expr) => Expression::IsNull(substitute_box(expr)?),
+    Expression::Math(op, l, r) => Expression::Math(op, substitute_box(l)?, substitute_box(r)?),
+    Expression::Concat(exprs) => Expression::Concat(substitute_vec(exprs)?),
+    Expression::Object(fields) => Expression::Object(
+      fields
+        .into_iter()
+        .map(|(name, expr)| Ok((name, substitute(expr)?)))
+        .collect::<Result<_, String>>()?,
+    ),
+    Expression::List(exprs) => Expression::List(substitute_vec(exprs)?),
+    Expression::Pipe(steps) => Expression::Pipe(substitute_vec(steps)?),
+    Expression::Transform(expr, transform) => Expression::Transform(substitute_box(expr)?, transform),
+    expression @ (Expression::Context(_) | Expression::Literal(_)) => expression,
+  })
+}
+
+fn to_pipe_step_expression(
+  config: &Config,
+  field: &Field,
+  step: &PipeStep,
+  is_last: bool,
+) -> Valid<Expression, String> {
+  match step {
+    PipeStep::Http(http) => {
+      let output_schema = if is_last {
+        to_output_schema(field, config)
+      } else {
+        JsonSchema::Any
+      };
+      to_http_expression(config, field, http, output_schema).trace("http")
+    }
+    PipeStep::Expr(expr) => to_expression(&expr.body, field, config).trace("expr"),
+    PipeStep::Unsafe(op) => Valid::succeed(Lambda::context().to_unsafe_js(op.script.clone()).expression),
+    PipeStep::Const(const_field) => Valid::succeed(Literal(const_field.data.clone())),
+  }
+}
+
+fn to_expression(body: &ExprBody, field: &Field, config: &Config) -> Valid<Expression, String> {
+  let to_box = |body: &ExprBody| to_expression(body, field, config).map(Box::new);
+  let to_pair = |left: &ExprBody, right: &ExprBody| to_box(left).zip(to_box(right));
+  let to_vec = |bodies: &Vec<ExprBody>| Valid::from_iter(bodies, |body| to_expression(body, field, config));
+
+  match body {
+    ExprBody::If { cond, then, els } => to_box(cond)
+      .zip(to_box(then))
+      .zip(to_box(els))
+      .map(|((cond, then), els)| Expression::If { cond, then, els })
+      .trace("if"),
+    ExprBody::And(bodies) => to_vec(bodies).map(Expression::And).trace("and"),
+    ExprBody::Or(bodies) => to_vec(bodies).map(Expression::Or).trace("or"),
+    ExprBody::Not(body) => to_box(body).map(Expression::Not).trace("not"),
+    ExprBody::Eq(l, r) => to_pair(l, r).map(|(l, r)| Expression::EqualTo(l, r)).trace("eq"),
+    ExprBody::Gt(l, r) => to_pair(l, r).map(|(l, r)| Expression::Gt(l, r)).trace("gt"),
+    ExprBody::Gte(l, r) => to_pair(l, r).map(|(l, r)| Expression::Gte(l, r)).trace("gte"),
+    ExprBody::Lt(l, r) => to_pair(l, r).map(|(l, r)| Expression::Lt(l, r)).trace("lt"),
+    ExprBody::Lte(l, r) => to_pair(l, r).map(|(l, r)| Expression::Lte(l, r)).trace("lte"),
+    ExprBody::Contains(l, r) => to_pair(l, r).map(|(l, r)| Expression::Contains(l, r)).trace("contains"),
+    ExprBody::IsNull(body) => to_box(body).map(Expression::IsNull).trace("isNull"),
+    ExprBody::Add(l, r) => to_pair(l, r)
+      .map(|(l, r)| Expression::Math(Math::Add, l, r))
+      .trace("add"),
+    ExprBody::Sub(l, r) => to_pair(l, r)
+      .map(|(l, r)| Expression::Math(Math::Sub, l, r))
+      .trace("sub"),
+    ExprBody::Mul(l, r) => to_pair(l, r)
+      .map(|(l, r)| Expression::Math(Math::Mul, l, r))
+      .trace("mul"),
+    ExprBody::Div(l, r) => to_pair(l, r)
+      .map(|(l, r)| Expression::Math(Math::Div, l, r))
+      .trace("div"),
+    ExprBody::Concat(bodies) => to_vec(bodies).map(Expression::Concat).trace("concat"),
+    ExprBody::Object(fields) => Valid::from_iter(fields, |(name, body)| {
+      to_expression(body, field, config)
+        .map(|expr| (name.clone(), expr))
+        .trace(name)
+    })
+    .map(Expression::Object)
+    .trace("object"),
+    ExprBody::List(bodies) => to_vec(bodies).map(Expression::List).trace("list"),
+    ExprBody::Path(path) => to_context(path, field, config).map(Expression::Context).trace("path"),
+    ExprBody::Const(value) => Valid::succeed(Expression::Literal(value.clone())),
+  }
+}
+
+fn to_context(path: &[String], field: &Field, config: &Config) -> Valid<Context, String> {
+  match path.split_first() {
+    Some((head, tail)) => match (head.as_str(), tail) {
+      ("value", []) => Valid::succeed(Context::Value),
+      ("value", tail) => Valid::succeed(Context::Path(tail.to_vec())),
+      ("args", [name, ..]) if field.args.contains_key(name) => Valid::succeed(Context::Args(tail.to_vec())),
+      ("args", [name, ..]) => Valid::fail(format!("no argument '{name}' found")),
+      ("vars", [name]) if config.server.vars.get(name).is_some() => Valid::succeed(Context::Var(name.clone())),
+      ("vars", [name]) => Valid::fail(format!("var '{name}' is not set in the server config")),
+      ("headers", [name]) => Valid::succeed(Context::Header(name.clone())),
+      ("args" | "vars" | "headers", _) => Valid::fail(format!("invalid path '{}'", path.join("."))),
+      (head, _) => Valid::fail(format!("unknown template directive '{head}'")),
+    },
+    None => Valid::fail("path can't be empty".to_string()),
+  }
+}
+
 fn is_scalar(type_name: &str) -> bool {
-  ["String", "Int", "Float", "Boolean", "ID", "JSON"].contains(&type_name)
+  ["String", "Int", "Float", "Boolean", "ID", "JSON"].contains(&type_name) || Scalar::find(type_name).is_some()
 }
 
 type InvalidPathHandler = dyn Fn(&str, &[String]) -> Valid<Type, String>;
@@ -606,11 +1257,7 @@ fn process_field_within_type(
     if next_field.has_resolver() {
       return Valid::<Type, String>::fail(format!(
         "Inline can't be done because of {} resolver at [{}.{}]",
-        {
-          let next_dir_http = next_field.http.as_ref().map(|_| "http");
-          let next_dir_const = next_field.const_field.as_ref().map(|_| "const");
-          next_dir_http.or(next_dir_const).unwrap_or("unsafe")
-        },
+        next_field.resolvable_directives()[0].trim_start_matches('@'),
         field.type_of,
         field_name
       ))
@@ -722,6 +1369,14 @@ fn update_args<'a>() -> TryFold<'a, (&'a Config, &'a Field, &'a config::Type, &'
     })
   })
 }
+/// The schema of the data resolved for a field, before it's reshaped by `@transform` into the type of the field.
+fn to_output_schema(field: &Field, config: &Config) -> JsonSchema {
+  match field.transform {
+    Some(_) => JsonSchema::Any,
+    None => to_json_schema_for_field(field, config),
+  }
+}
+
 pub fn to_json_schema_for_field(field: &Field, config: &Config) -> JsonSchema {
   to_json_schema(&field.type_of, field.required, field.list, config)
 }
@@ -738,10 +1393,22 @@ pub fn to_json_schema_for_args(args: &BTreeMap<String, Arg>, config: &Config) ->
 pub fn to_json_schema(type_of: &str, required: bool, list: bool, config: &Config) -> JsonSchema {
   let type_ = config.find_type(type_of);
   let schema = match type_ {
+    // Aliases can be numbers
+    Some(type_) if type_.variants.is_some() && !type_.variant_alias.is_empty() => JsonSchema::Any,
+    Some(type_) if type_.variants.is_some() => JsonSchema::Str,
+    // Scalars declared by the config aren't validated, even when they're named like a built-in one
+    Some(type_) if type_.scalar => JsonSchema::Any,
     Some(type_) => {
       let mut schema_fields = HashMap::new();
       for (name, field) in type_.fields.iter() {
-        if field.unsafe_operation.is_none() && field.http.is_none() {
+        if field.unsafe_operation.is_none()
+          && field.http.is_none()
+          && field.expr.is_none()
+          && field.pipe.is_none()
+          && field.call.is_none()
+          && field.wasm.is_none()
+          && field.rust.is_none()
+        {
           schema_fields.insert(name.clone(), to_json_schema_for_field(field, config));
         }
       }
@@ -752,7 +1419,7 @@ pub fn to_json_schema(type_of: &str, required: bool, list: bool, config: &Config
       "Int" => JsonSchema::Num {},
       "Boolean" => JsonSchema::Bool {},
       "JSON" => JsonSchema::Obj(HashMap::new()),
-      _ => JsonSchema::Str {},
+      type_of => Scalar::find(type_of).map_or(JsonSchema::Str, JsonSchema::Format),
     },
   };
 
@@ -776,3 +1443,93 @@ impl TryFrom<&Config> for Blueprint {
     config_blueprint().try_fold(config, Blueprint::default()).to_result()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_substitute_args_in_endpoint() {
+    let endpoint = Endpoint::new("http://localhost/users/{{args.id}}".to_string())
+      .query(vec![("page".to_string(), "{{args.page}}".to_string())]);
+    let resolver = Lambda::from_request_template(RequestTemplate::try_from(endpoint).unwrap()).expression;
+    let args = BTreeMap::from([(
+      "id".to_string(),
+      (
+        serde_json::json!("{{value.userId}}"),
+        Mustache::parse("{{value.userId}}").unwrap(),
+      ),
+    )]);
+
+    let Expression::Unsafe(Operation::Endpoint(req_template, _, _)) = substitute_args(resolver, &args).unwrap() else {
+      panic!("expected an endpoint")
+    };
+    assert_eq!(
+      req_template.root_url,
+      Mustache::parse("http://localhost/users/{{value.userId}}").unwrap()
+    );
+    assert_eq!(req_template.query[0].1, Mustache::from(vec![]));
+  }
+
+  #[test]
+  fn test_substitute_args_in_context() {
+    let args = BTreeMap::from([
+      ("id".to_string(), (serde_json::json!(1), Mustache::parse("1").unwrap())),
+      (
+        "user".to_string(),
+        (
+          serde_json::json!("{{value.user}}"),
+          Mustache::parse("{{value.user}}").unwrap(),
+        ),
+      ),
+    ]);
+    let resolver = Expression::List(vec![
+      Expression::Context(Context::Args(vec!["id".to_string()])),
+      Expression::Context(Context::Args(vec!["user".to_string(), "name".to_string()])),
+    ]);
+
+    let Expression::List(items) = substitute_args(resolver, &args).unwrap() else {
+      panic!("expected a list")
+    };
+    assert!(matches!(&items[0], Expression::Literal(value) if value == &serde_json::json!(1)));
+    assert!(matches!(&items[1], Expression::Context(Context::Path(path)) if path == &["user", "name"]));
+  }
+
+  #[test]
+  fn test_http_output_schema_with_transform() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+        }
+
+        type Query {
+          users: [User] @http(path: "/users") @transform(path: ["data", "items"])
+        }
+
+        type User {
+          id: Int
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    let blueprint = Blueprint::try_from(&config).unwrap();
+    let resolver = blueprint
+      .definitions
+      .iter()
+      .find_map(|def| match def {
+        Definition::ObjectTypeDefinition(def) if def.name == "Query" => def.fields[0].resolver.clone(),
+        _ => None,
+      })
+      .unwrap();
+
+    let Expression::Transform(resolver, _) = resolver else {
+      panic!("expected a transform")
+    };
+    let Expression::Unsafe(Operation::Endpoint(req_template, _, _)) = *resolver else {
+      panic!("expected an endpoint")
+    };
+    assert_eq!(req_template.endpoint.output, JsonSchema::Any);
+  }
+}
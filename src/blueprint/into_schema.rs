@@ -1,14 +1,17 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use async_graphql::dynamic::{
-  FieldFuture, FieldValue, SchemaBuilder, {self},
+  FieldFuture, FieldValue, ResolverContext, SchemaBuilder, {self},
 };
 use async_graphql_value::ConstValue;
 
+use super::enum_mapping::EnumMapping;
+use super::scalar_args::ScalarArgs;
 use crate::blueprint::{Blueprint, Definition, Type};
 use crate::http::RequestContext;
-use crate::lambda::EvaluationContext;
+use crate::lambda::{EvaluationContext, ObjectValue, WithArgs};
 
 fn to_type_ref(type_of: &Type) -> dynamic::TypeRef {
   match type_of {
@@ -30,7 +33,37 @@ fn to_type_ref(type_of: &Type) -> dynamic::TypeRef {
   }
 }
 
-fn to_type(def: &Definition) -> dynamic::Type {
+/// Objects keep the value of the object they were resolved from, for the templates that refer to `parent`.
+fn to_field_value(value: ConstValue, ctx: &ResolverContext, is_object: bool) -> FieldValue<'static> {
+  if !is_object {
+    return match value {
+      ConstValue::List(a) => FieldValue::list(a),
+      a => FieldValue::from(a),
+    };
+  }
+  let parent = match ctx.parent_value.downcast_ref::<ObjectValue>() {
+    Some(object) => Some(object.value.clone()),
+    None => ctx.parent_value.as_value().cloned().map(Arc::new),
+  };
+  to_object_value(value, &parent)
+}
+
+fn to_object_value(value: ConstValue, parent: &Option<Arc<ConstValue>>) -> FieldValue<'static> {
+  match value {
+    ConstValue::List(values) => FieldValue::list(values.into_iter().map(|value| to_object_value(value, parent))),
+    ConstValue::Object(_) => FieldValue::owned_any(ObjectValue { value: Arc::new(value), parent: parent.clone() }),
+    value => FieldValue::from(value),
+  }
+}
+
+/// What the resolvers read from the blueprint as a whole.
+struct SchemaContext {
+  objects: HashSet<String>,
+  enum_mapping: Arc<EnumMapping>,
+  scalar_args: Arc<ScalarArgs>,
+}
+
+fn to_type(def: &Definition, schema_ctx: &SchemaContext) -> dynamic::Type {
   match def {
     Definition::ObjectTypeDefinition(def) => {
       let mut object = dynamic::Object::new(def.name.clone());
@@ -38,26 +71,43 @@ fn to_type(def: &Definition) -> dynamic::Type {
         let field = field.clone();
         let type_ref = to_type_ref(&field.of_type);
         let field_name = &field.name.clone();
+        let is_object = schema_ctx.objects.contains(field.of_type.name());
+        let maps_args = field.args.iter().any(|arg| schema_ctx.enum_mapping.maps(&arg.of_type));
+        let maps_value = schema_ctx.enum_mapping.maps(&field.of_type);
+        let validates_args = field
+          .args
+          .iter()
+          .any(|arg| schema_ctx.scalar_args.validates(&arg.of_type));
+        let enum_mapping = schema_ctx.enum_mapping.clone();
+        let scalar_args = schema_ctx.scalar_args.clone();
+        let definition = Arc::new(field.clone());
         let mut dyn_schema_field = dynamic::Field::new(field_name, type_ref, move |ctx| {
           let req_ctx = ctx.ctx.data::<Arc<RequestContext>>().unwrap();
-          let field_name = field.name.clone();
-          let resolver = field.resolver.clone();
+          let field = definition.clone();
+          let enum_mapping = enum_mapping.clone();
+          let scalar_args = scalar_args.clone();
           FieldFuture::new(async move {
-            match resolver {
-              None => {
-                let ctx = EvaluationContext::new(req_ctx, &ctx);
-                Ok(ctx.path_value(&[field_name]).map(|a| FieldValue::from(a.to_owned())))
-              }
-              Some(expr) => {
-                let ctx = EvaluationContext::new(req_ctx, &ctx);
-                let const_value = expr.eval(&ctx).await?;
-                let p = match const_value {
-                  ConstValue::List(a) => FieldValue::list(a),
-                  a => FieldValue::from(a),
-                };
-                Ok(Some(p))
-              }
+            if validates_args {
+              scalar_args
+                .validate(&field.args, ctx.args.as_index_map())
+                .map_err(async_graphql::Error::new)?;
             }
+            let value = match &field.resolver {
+              None => EvaluationContext::new(req_ctx, &ctx)
+                .path_value(&[&field.name])
+                .cloned(),
+              Some(expr) if maps_args => {
+                let args = enum_mapping.args_to_upstream(&field.args, ctx.args.as_index_map());
+                let upstream_ctx = WithArgs { ctx: &ctx, args };
+                Some(expr.eval(&EvaluationContext::new(req_ctx, &upstream_ctx)).await?)
+              }
+              Some(expr) => Some(expr.eval(&EvaluationContext::new(req_ctx, &ctx)).await?),
+            };
+            let value = match value {
+              Some(value) if maps_value => Some(enum_mapping.value_from_upstream(&field.of_type, value)),
+              value => value,
+            };
+            Ok(value.map(|value| to_field_value(value, &ctx, is_object)))
           })
         });
         if let Some(description) = &field.description {
@@ -102,6 +152,9 @@ fn to_type(def: &Definition) -> dynamic::Type {
       if let Some(description) = &def.description {
         scalar = scalar.description(description);
       }
+      if let Some(url) = def.scalar.and_then(|scalar| scalar.specified_by()) {
+        scalar = scalar.specified_by_url(url);
+      }
       dynamic::Type::Scalar(scalar)
     }
     Definition::EnumTypeDefinition(def) => {
@@ -126,8 +179,21 @@ fn create(blueprint: &Blueprint) -> SchemaBuilder {
   let mutation = blueprint.mutation();
   let mut schema = dynamic::Schema::build(query.as_str(), mutation.as_deref(), None);
 
+  let objects = blueprint
+    .definitions
+    .iter()
+    .filter_map(|def| match def {
+      Definition::ObjectTypeDefinition(def) => Some(def.name.clone()),
+      _ => None,
+    })
+    .collect::<HashSet<_>>();
+  let schema_ctx = SchemaContext {
+    objects,
+    enum_mapping: Arc::new(EnumMapping::new(blueprint)),
+    scalar_args: Arc::new(ScalarArgs::new(blueprint)),
+  };
   for def in blueprint.definitions.iter() {
-    schema = schema.register(to_type(def));
+    schema = schema.register(to_type(def, &schema_ctx));
   }
 
   schema
@@ -1,7 +1,10 @@
 mod blueprint;
 mod compress;
+mod enum_mapping;
 mod from_config;
 mod into_schema;
+mod rename;
+mod scalar_args;
 mod timeout;
 
 // TODO: make it private
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use super::{Definition, Type};
+
+// rename_types() renames the definitions of types along with every reference to them, for the types renamed
+// with a type level `@modify(name:)`.
+pub fn rename_types(definitions: Vec<Definition>, renames: &HashMap<String, String>) -> Vec<Definition> {
+  if renames.is_empty() {
+    return definitions;
+  }
+  let rename = |name: &mut String| {
+    if let Some(new_name) = renames.get(name) {
+      *name = new_name.clone();
+    }
+  };
+
+  definitions
+    .into_iter()
+    .map(|mut def| {
+      match &mut def {
+        Definition::ObjectTypeDefinition(def) => {
+          rename(&mut def.name);
+          for field in def.fields.iter_mut() {
+            rename_type(&mut field.of_type, renames);
+            for arg in field.args.iter_mut() {
+              rename_type(&mut arg.of_type, renames);
+            }
+          }
+          def.implements = def
+            .implements
+            .iter()
+            .map(|name| renames.get(name).unwrap_or(name).clone())
+            .collect();
+        }
+        Definition::InterfaceTypeDefinition(def) => {
+          rename(&mut def.name);
+          for field in def.fields.iter_mut() {
+            rename_type(&mut field.of_type, renames);
+          }
+        }
+        Definition::InputObjectTypeDefinition(def) => {
+          rename(&mut def.name);
+          for field in def.fields.iter_mut() {
+            rename_type(&mut field.of_type, renames);
+          }
+        }
+        Definition::ScalarTypeDefinition(def) => rename(&mut def.name),
+        Definition::EnumTypeDefinition(def) => rename(&mut def.name),
+        Definition::UnionTypeDefinition(def) => {
+          rename(&mut def.name);
+          def.types = def
+            .types
+            .iter()
+            .map(|name| renames.get(name).unwrap_or(name).clone())
+            .collect();
+        }
+      }
+      def
+    })
+    .collect()
+}
+
+fn rename_type(of_type: &mut Type, renames: &HashMap<String, String>) {
+  match of_type {
+    Type::NamedType { name, .. } => {
+      if let Some(new_name) = renames.get(name) {
+        *name = new_name.clone();
+      }
+    }
+    Type::ListType { of_type, .. } => rename_type(of_type, renames),
+  }
+}
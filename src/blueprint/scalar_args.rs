@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use async_graphql::{Name, Value};
+use indexmap::IndexMap;
+
+use super::{Blueprint, Definition, InputFieldDefinition, Type};
+use crate::scalar::Scalar;
+
+/// Validates the values of built-in scalars in arguments, including the ones nested in input objects.
+/// The scalars aren't validated by the schema, since that would validate the values upstreams respond with too.
+/// Scalars declared by the config are left alone, even when they're named like a built-in one.
+#[derive(Default)]
+pub struct ScalarArgs {
+  scalars: HashMap<String, Scalar>,
+  inputs: HashMap<String, Vec<InputFieldDefinition>>,
+}
+
+/// A value that isn't valid for its scalar, along with the path to it from the innermost field.
+struct InvalidValue {
+  path: Vec<String>,
+  scalar: Scalar,
+}
+
+impl ScalarArgs {
+  pub fn new(blueprint: &Blueprint) -> Self {
+    let mut scalars = HashMap::new();
+    let mut inputs = HashMap::new();
+    for def in blueprint.definitions.iter() {
+      match def {
+        Definition::ScalarTypeDefinition(def) => {
+          if let Some(scalar) = def.scalar {
+            scalars.insert(def.name.clone(), scalar);
+          }
+        }
+        Definition::InputObjectTypeDefinition(def) => {
+          inputs.insert(def.name.clone(), def.fields.clone());
+        }
+        _ => {}
+      }
+    }
+    ScalarArgs { scalars, inputs }
+  }
+
+  /// Whether values of the type can hold built-in scalars.
+  pub fn validates(&self, of_type: &Type) -> bool {
+    self.validates_named(of_type.name(), &mut Vec::new())
+  }
+
+  fn validates_named<'a>(&'a self, name: &'a str, seen: &mut Vec<&'a str>) -> bool {
+    if self.scalars.contains_key(name) {
+      return true;
+    }
+    if seen.contains(&name) {
+      return false;
+    }
+    seen.push(name);
+    match self.inputs.get(name) {
+      Some(fields) => fields
+        .iter()
+        .any(|field| self.validates_named(field.of_type.name(), seen)),
+      None => false,
+    }
+  }
+
+  pub fn validate(&self, args: &[InputFieldDefinition], values: &IndexMap<Name, Value>) -> Result<(), String> {
+    for arg in args {
+      if let Some(value) = values.get(arg.name.as_str()) {
+        self.validate_value(arg.of_type.name(), value).map_err(|mut invalid| {
+          invalid.path.push(arg.name.clone());
+          invalid.path.reverse();
+          format!(
+            "Invalid value for argument \"{}\", {}",
+            invalid.path.join("."),
+            invalid.scalar.expected()
+          )
+        })?;
+      }
+    }
+    Ok(())
+  }
+
+  fn validate_value(&self, type_name: &str, value: &Value) -> Result<(), InvalidValue> {
+    match value {
+      Value::Null => Ok(()),
+      Value::List(items) => items.iter().try_for_each(|item| self.validate_value(type_name, item)),
+      value => match (self.scalars.get(type_name), self.inputs.get(type_name), value) {
+        (Some(scalar), _, value) if !scalar.validate(value) => Err(InvalidValue { path: Vec::new(), scalar: *scalar }),
+        (None, Some(fields), Value::Object(map)) => {
+          fields.iter().try_for_each(|field| match map.get(field.name.as_str()) {
+            Some(value) => self.validate_value(field.of_type.name(), value).map_err(|mut invalid| {
+              invalid.path.push(field.name.clone());
+              invalid
+            }),
+            None => Ok(()),
+          })
+        }
+        _ => Ok(()),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use async_graphql::{Name, Value};
+  use indexmap::IndexMap;
+  use pretty_assertions::assert_eq;
+
+  use super::ScalarArgs;
+  use crate::blueprint::{
+    Blueprint, Definition, InputFieldDefinition, InputObjectTypeDefinition, ScalarTypeDefinition, Type,
+  };
+  use crate::scalar::Scalar;
+
+  fn input_field(name: &str, type_name: &str) -> InputFieldDefinition {
+    InputFieldDefinition {
+      name: name.to_string(),
+      of_type: Type::NamedType { name: type_name.to_string(), non_null: false },
+      default_value: None,
+      description: None,
+    }
+  }
+
+  fn scalar(name: &str, scalar: Option<Scalar>) -> Definition {
+    Definition::ScalarTypeDefinition(ScalarTypeDefinition {
+      name: name.to_string(),
+      directive: Vec::new(),
+      description: None,
+      scalar,
+    })
+  }
+
+  #[test]
+  fn test_validate() {
+    let contact = InputObjectTypeDefinition {
+      name: "Contact".to_string(),
+      fields: vec![input_field("email", "Email")],
+      description: None,
+    };
+    let blueprint = Blueprint::default().definitions(vec![
+      Definition::InputObjectTypeDefinition(contact),
+      scalar("UUID", Some(Scalar::Uuid)),
+      scalar("Email", Some(Scalar::Email)),
+    ]);
+    let scalar_args = ScalarArgs::new(&blueprint);
+    let args = vec![input_field("id", "UUID"), input_field("contact", "Contact")];
+    assert!(scalar_args.validates(&args[1].of_type));
+
+    let contact = |email: &str| Value::Object(IndexMap::from([(Name::new("email"), Value::from(email))]));
+    let values = IndexMap::from([
+      (Name::new("id"), Value::from("123e4567-e89b-12d3-a456-426614174000")),
+      (Name::new("contact"), contact("john@example.com")),
+    ]);
+    assert_eq!(scalar_args.validate(&args, &values), Ok(()));
+
+    let values = IndexMap::from([(Name::new("contact"), contact("john"))]);
+    assert_eq!(
+      scalar_args.validate(&args, &values),
+      Err("Invalid value for argument \"contact.email\", expected an email address".to_string())
+    );
+  }
+
+  #[test]
+  fn test_declared_scalar() {
+    let blueprint = Blueprint::default().definitions(vec![scalar("Email", None)]);
+    let scalar_args = ScalarArgs::new(&blueprint);
+    let args = vec![input_field("email", "Email")];
+    assert!(!scalar_args.validates(&args[0].of_type));
+
+    let values = IndexMap::from([(Name::new("email"), Value::from("john"))]);
+    assert_eq!(scalar_args.validate(&args, &values), Ok(()));
+  }
+}
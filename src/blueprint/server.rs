@@ -1,5 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::net::{AddrParseError, IpAddr};
+use std::time::Duration;
 
 use derive_setters::Setters;
 use hyper::header::{HeaderName, HeaderValue};
@@ -16,8 +17,12 @@ pub struct Server {
   pub enable_introspection: bool,
   pub enable_query_validation: bool,
   pub enable_response_validation: bool,
+  /// The allowed environment variables, read when the server starts.
+  pub env: BTreeMap<String, String>,
   pub global_response_timeout: i64,
   pub port: u16,
+  pub script_timeout: Duration,
+  pub script_workers: usize,
   pub hostname: IpAddr,
   pub vars: BTreeMap<String, String>,
   pub response_headers: HeaderMap,
@@ -53,15 +58,19 @@ impl TryFrom<crate::config::Server> for Server {
   fn try_from(config_server: config::Server) -> Result<Self, Self::Error> {
     validate_hostname((config_server).get_hostname().to_lowercase())
       .zip(handle_response_headers((config_server).get_response_headers().0))
-      .map(|(hostname, response_headers)| Server {
+      .zip(read_env((config_server).get_env()))
+      .map(|((hostname, response_headers), env)| Server {
         enable_apollo_tracing: (config_server).enable_apollo_tracing(),
         enable_cache_control_header: (config_server).enable_cache_control(),
         enable_graphiql: (config_server).enable_graphiql(),
         enable_introspection: (config_server).enable_introspection(),
         enable_query_validation: (config_server).enable_query_validation(),
         enable_response_validation: (config_server).enable_http_validation(),
+        env,
         global_response_timeout: (config_server).get_global_response_timeout(),
         port: (config_server).get_port(),
+        script_timeout: Duration::from_millis((config_server).get_script_timeout()),
+        script_workers: (config_server).get_script_workers(),
         hostname,
         vars: (config_server).get_vars(),
         response_headers,
@@ -70,6 +79,17 @@ impl TryFrom<crate::config::Server> for Server {
   }
 }
 
+fn read_env(names: BTreeSet<String>) -> Valid<BTreeMap<String, String>, String> {
+  Valid::from_iter(names, |name| match std::env::var(&name) {
+    Ok(value) => Valid::succeed((name, value)),
+    Err(_) => Valid::fail(format!("environment variable '{}' is not set", name)),
+  })
+  .map(|env| env.into_iter().collect())
+  .trace("env")
+  .trace("@server")
+  .trace("schema")
+}
+
 fn validate_hostname(hostname: String) -> Valid<IpAddr, String> {
   if hostname == "localhost" {
     Valid::succeed(IpAddr::from([127, 0, 0, 1]))
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::blueprint::{Blueprint, Definition};
+use crate::cli::CLIError;
+use crate::config::Config;
+use crate::http::{serve, HttpClient, ServerContext, TailcallService};
+use crate::lambda::{Expression, Operation, Resolver, Resolvers};
+use crate::valid::{Valid, ValidationError};
+
+/// Builds a server from a [`Config`] or a [`Blueprint`], along with what can't
+/// be expressed in one, such as the resolvers implemented in Rust.
+pub struct TailcallBuilder {
+  blueprint: Result<Blueprint, ValidationError<String>>,
+  resolvers: Resolvers,
+  http_client: Option<Arc<dyn HttpClient>>,
+}
+
+impl TailcallBuilder {
+  pub fn new(config: Config) -> Self {
+    Self { blueprint: Blueprint::try_from(&config), resolvers: Resolvers::default(), http_client: None }
+  }
+
+  pub fn from_blueprint(blueprint: Blueprint) -> Self {
+    Self { blueprint: Ok(blueprint), resolvers: Resolvers::default(), http_client: None }
+  }
+
+  /// Sends the upstream requests with `http_client` instead of a client built from the `@upstream` settings.
+  pub fn http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+    self.http_client = Some(Arc::new(http_client));
+    self
+  }
+
+  /// Registers a resolver that fields can use with `@rust(name: "...")`.
+  pub fn resolver(mut self, name: impl Into<String>, resolver: impl Resolver + 'static) -> Self {
+    self.resolvers.insert(name, resolver);
+    self
+  }
+
+  pub fn build(self) -> Result<ServerContext, ValidationError<String>> {
+    let mut blueprint = self.blueprint?;
+    assign_resolvers(&mut blueprint, &self.resolvers).to_result()?;
+    Ok(match self.http_client {
+      Some(http_client) => ServerContext::with_http_client(blueprint, http_client),
+      None => ServerContext::new(blueprint),
+    })
+  }
+
+  /// Builds the server as a service that can be mounted in an existing hyper or tower app.
+  pub fn build_service(self) -> Result<TailcallService, ValidationError<String>> {
+    Ok(TailcallService::new(self.build()?))
+  }
+
+  /// Builds the server and serves it on the configured hostname and port.
+  pub async fn start(self) -> Result<()> {
+    serve(self.build().map_err(CLIError::from)?).await
+  }
+}
+
+fn assign_resolvers(blueprint: &mut Blueprint, resolvers: &Resolvers) -> Valid<(), String> {
+  Valid::from_iter(blueprint.definitions.iter_mut(), |def| match def {
+    Definition::ObjectTypeDefinition(def) => {
+      let type_name = def.name.clone();
+      Valid::from_iter(def.fields.iter_mut(), |field| {
+        let field_name = field.name.clone();
+        match &mut field.resolver {
+          Some(expression) => assign_resolver(expression, resolvers).trace(&field_name),
+          None => Valid::succeed(()),
+        }
+      })
+      .trace(&type_name)
+      .unit()
+    }
+    _ => Valid::succeed(()),
+  })
+  .unit()
+}
+
+fn assign_resolver(expression: &mut Expression, resolvers: &Resolvers) -> Valid<(), String> {
+  match expression {
+    Expression::Unsafe(Operation::Rust(name, resolver)) => match resolvers.get(name) {
+      Some(registered) => {
+        *resolver = Some(registered);
+        Valid::succeed(())
+      }
+      None => Valid::fail(format!("no resolver named '{}' is registered", name)).trace("@rust"),
+    },
+    Expression::Pipe(steps) => Valid::from_iter(steps.iter_mut(), |step| assign_resolver(step, resolvers)).unit(),
+    Expression::Transform(expr, _) => assign_resolver(expr, resolvers),
+    _ => Valid::succeed(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use anyhow::Result;
+  use async_graphql::Value;
+
+  use super::TailcallBuilder;
+  use crate::config::Config;
+  use crate::http::{MockHttpClient, RequestContext, Response};
+  use crate::lambda::{DynResolverContext, EvaluationContext, Resolver};
+  use crate::valid::ValidationError;
+
+  struct Greet;
+
+  #[async_trait::async_trait]
+  impl Resolver for Greet {
+    async fn resolve<'a>(&self, ctx: &EvaluationContext<'a, DynResolverContext<'a>>) -> Result<Value> {
+      let name = ctx.arg(&["name"]).cloned().unwrap_or(Value::from("world"));
+      Ok(Value::from(format!("Hello {}!", name.to_string().trim_matches('"'))))
+    }
+  }
+
+  const CONFIG: &str = r#"
+    schema {
+      query: Query
+    }
+
+    type Query {
+      greet(name: String): String @rust(name: "greet")
+    }
+  "#;
+
+  #[tokio::test]
+  async fn test_rust_resolver() {
+    let config = Config::from_sdl(CONFIG).to_result().unwrap();
+    let server_ctx = TailcallBuilder::new(config).resolver("greet", Greet).build().unwrap();
+    let req_ctx = Arc::new(RequestContext::from(&server_ctx));
+    let request = async_graphql::Request::new(r#"{ greet(name: "John") }"#).data(req_ctx);
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.data.into_json().unwrap(),
+      serde_json::json!({"greet": "Hello John!"})
+    );
+  }
+
+  #[tokio::test]
+  async fn test_execute_service() {
+    let config = Config::from_sdl(CONFIG).to_result().unwrap();
+    let service = TailcallBuilder::new(config)
+      .resolver("greet", Greet)
+      .build_service()
+      .unwrap();
+    let request = hyper::Request::post("/graphql")
+      .body(hyper::Body::from(r#"{"query": "{ greet }"}"#))
+      .unwrap();
+    let response = service.execute(request).await.unwrap();
+    assert_eq!(response.status(), hyper::StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, serde_json::json!({"data": {"greet": "Hello world!"}}));
+  }
+
+  #[tokio::test]
+  async fn test_custom_http_client() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+        }
+
+        type Query {
+          user: User @http(path: "/users/1")
+        }
+
+        type User {
+          id: Int
+          path: String
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    let server_ctx = TailcallBuilder::new(config)
+      .http_client(MockHttpClient(|req: &reqwest::Request| {
+        let body = serde_json::json!({"id": 1, "path": req.url().path()});
+        Ok(Response::default().body(Value::from_json(body)?))
+      }))
+      .build()
+      .unwrap();
+    let req_ctx = Arc::new(RequestContext::from(&server_ctx));
+    let request = async_graphql::Request::new("{ user { id path } }").data(req_ctx);
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.data.into_json().unwrap(),
+      serde_json::json!({"user": {"id": 1, "path": "/users/1"}})
+    );
+  }
+
+  #[test]
+  fn test_unregistered_resolver() {
+    let config = Config::from_sdl(CONFIG).to_result().unwrap();
+    let error = TailcallBuilder::new(config).build().err().unwrap();
+    let expected = ValidationError::new("no resolver named 'greet' is registered".to_string())
+      .trace("@rust")
+      .trace("greet")
+      .trace("Query");
+    assert_eq!(error, expected);
+  }
+}
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
 
 use anyhow::Result;
 use async_graphql::futures_util::future::join_all;
@@ -9,12 +10,14 @@ use serde_json::Value;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 
-use super::{Server, Upstream};
+use super::{Server, Transform, Upstream};
+use crate::config::group_by::{BatchEncoding, GroupByKeys, OnMissingKey};
 use crate::config::source::Source;
 use crate::config::{is_default, KeyValues};
 use crate::http::Method;
 use crate::json::JsonSchema;
 use crate::valid::Valid;
+use crate::wasm::WasmLimits;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default, Setters)]
 #[serde(rename_all = "camelCase")]
@@ -127,6 +130,14 @@ pub struct Type {
   pub variants: Option<BTreeSet<String>>,
   #[serde(default)]
   pub scalar: bool,
+  pub modify: Option<ModifyField>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub variant_modify: BTreeMap<String, ModifyField>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub variant_alias: BTreeMap<String, Alias>,
+  pub omit: Option<Omit>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub added_fields: Vec<AddField>,
 }
 
 impl Type {
@@ -149,6 +160,11 @@ impl Type {
     } else {
       self.variants = other.variants.clone();
     }
+    self.variant_modify.extend(other.variant_modify.clone());
+    self.variant_alias.extend(other.variant_alias.clone());
+    self.modify = other.modify.clone().or(self.modify);
+    self.omit = other.omit.clone().or(self.omit);
+    self.added_fields.extend(other.added_fields.clone());
     Self { fields, ..self.clone() }
   }
 }
@@ -217,18 +233,31 @@ pub struct Field {
   pub doc: Option<String>,
   pub modify: Option<ModifyField>,
   pub inline: Option<InlineType>,
+  pub transform: Option<Transform>,
   pub http: Option<Http>,
   #[serde(rename = "unsafe")]
   pub unsafe_operation: Option<Unsafe>,
   pub const_field: Option<ConstField>,
+  pub expr: Option<Expr>,
+  pub pipe: Option<Pipe>,
+  pub call: Option<Call>,
+  pub wasm: Option<Wasm>,
+  pub rust: Option<Rust>,
 }
 
 impl Field {
   pub fn has_resolver(&self) -> bool {
-    self.http.is_some() || self.unsafe_operation.is_some() || self.const_field.is_some()
+    self.http.is_some()
+      || self.unsafe_operation.is_some()
+      || self.const_field.is_some()
+      || self.expr.is_some()
+      || self.pipe.is_some()
+      || self.call.is_some()
+      || self.wasm.is_some()
+      || self.rust.is_some()
   }
   pub fn resolvable_directives(&self) -> Vec<&str> {
-    let mut directives = Vec::with_capacity(3);
+    let mut directives = Vec::with_capacity(8);
     if self.http.is_some() {
       directives.push("@http")
     }
@@ -238,6 +267,21 @@ impl Field {
     if self.const_field.is_some() {
       directives.push("@const")
     }
+    if self.expr.is_some() {
+      directives.push("@expr")
+    }
+    if self.pipe.is_some() {
+      directives.push("@pipe")
+    }
+    if self.call.is_some() {
+      directives.push("@call")
+    }
+    if self.wasm.is_some() {
+      directives.push("@wasm")
+    }
+    if self.rust.is_some() {
+      directives.push("@rust")
+    }
     directives
   }
   pub fn has_batched_resolver(&self) -> bool {
@@ -254,7 +298,33 @@ pub struct Unsafe {
   pub script: String,
 }
 
+/// Resolves a field with a function exported by a WebAssembly module,
+/// eg. `@wasm(module: "plugin.wasm", function: "resolve")`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Wasm {
+  pub module: String,
+  pub function: String,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub fuel: Option<u64>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub memory_limit: Option<usize>,
+}
+
+impl Wasm {
+  /// Defaults to 10 million units of fuel and 16MiB of memory.
+  pub fn get_limits(&self) -> WasmLimits {
+    WasmLimits { fuel: self.fuel.unwrap_or(10_000_000), memory: self.memory_limit.unwrap_or(16 * 1024 * 1024) }
+  }
+}
+
+/// Resolves a field with a Rust resolver registered on the server under `name`.
 #[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Rust {
+  pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
 pub struct ModifyField {
   pub name: Option<String>,
   #[serde(default)]
@@ -262,6 +332,28 @@ pub struct ModifyField {
   pub omit: bool,
 }
 
+/// The values upstreams use for an enum value, eg. `ACTIVE @alias(options: ["active", 1])`.
+/// Any of them is read as the enum value, and the first one is sent for it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Alias {
+  pub options: Vec<Value>,
+}
+
+/// Hides fields of a type from the schema, while keeping them available to the templates of its other fields,
+/// eg. `@omit(fields: ["user_id"])`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Omit {
+  pub fields: Vec<String>,
+}
+
+/// Adds a field to a type that resolves to the value at `path`, starting from one of the type's fields,
+/// eg. `@addField(name: "street", path: ["address", "street"])`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct AddField {
+  pub name: String,
+  pub path: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct InlineType {
   pub path: Vec<String>,
@@ -303,7 +395,14 @@ pub struct Http {
   pub query: KeyValues,
   pub input: Option<JsonSchema>,
   pub output: Option<JsonSchema>,
-  pub body: Option<String>,
+  /// The request body: a template string sent as is, or a JSON value whose strings are templates.
+  pub body: Option<Value>,
+  #[serde(default)]
+  #[serde(skip_serializing_if = "is_default")]
+  pub encoding: Encoding,
+  /// Decodes responses in this format instead of the one of their `Content-Type`.
+  #[serde(rename = "responseFormat", skip_serializing_if = "is_default")]
+  pub response_format: Option<ResponseFormat>,
   #[serde(rename = "baseURL")]
   pub base_url: Option<String>,
   #[serde(default)]
@@ -311,7 +410,44 @@ pub struct Http {
   pub headers: KeyValues,
   #[serde(default)]
   #[serde(rename = "groupBy", skip_serializing_if = "is_default")]
-  pub group_by: Vec<String>,
+  pub group_by: GroupByKeys,
+  /// The path to the items that the `groupBy` keys are matched in, eg. `["data", "items"]`.
+  #[serde(default)]
+  #[serde(rename = "itemPath", skip_serializing_if = "is_default")]
+  pub item_path: Vec<String>,
+  #[serde(default)]
+  #[serde(rename = "batchKey", skip_serializing_if = "is_default")]
+  pub batch_key: Option<String>,
+  #[serde(default)]
+  #[serde(rename = "batchEncoding", skip_serializing_if = "is_default")]
+  pub batch_encoding: BatchEncoding,
+  #[serde(default)]
+  #[serde(rename = "onMissingKey", skip_serializing_if = "is_default")]
+  pub on_missing_key: OnMissingKey,
+}
+
+/// How the body of an `@http` request is serialized, which also sets its `Content-Type`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Encoding {
+  #[default]
+  ApplicationJson,
+  /// Sends the fields of an object body as `key=value` pairs, repeating the key for lists
+  FormUrlEncoded,
+  /// Sends each field of an object body as a part, forwarding `Upload` arguments as files
+  Multipart,
+  Text,
+}
+
+/// The format an upstream responds in.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ResponseFormat {
+  Json,
+  Xml,
+  Csv,
+  Ndjson,
+  Text,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -319,6 +455,81 @@ pub struct ConstField {
   pub data: Value,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Expr {
+  pub body: ExprBody,
+}
+
+/// Reuses the resolver of a field of the query (or mutation) type,
+/// eg. `@call(query: "user", args: {id: "{{value.userId}}"})`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Call {
+  #[serde(skip_serializing_if = "is_default")]
+  pub query: Option<String>,
+  #[serde(skip_serializing_if = "is_default")]
+  pub mutation: Option<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub args: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Pipe {
+  pub steps: Vec<PipeStep>,
+}
+
+/// A step of a `@pipe`, taking the same arguments as the directive it's named after,
+/// eg. `{http: {path: "/users/{{value.id}}"}}`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum PipeStep {
+  #[serde(serialize_with = "serialize_without_nulls")]
+  Http(Box<Http>),
+  Expr(Expr),
+  Unsafe(Unsafe),
+  Const(ConstField),
+}
+
+/// Leaves out the settings that aren't set, the way they're left out of the arguments of a directive.
+fn serialize_without_nulls<A: Serialize, S: serde::Serializer>(value: &A, serializer: S) -> Result<S::Ok, S::Error> {
+  let mut value = serde_json::to_value(value).map_err(serde::ser::Error::custom)?;
+  if let Value::Object(map) = &mut value {
+    map.retain(|_, value| !value.is_null());
+  }
+  value.serialize(serializer)
+}
+
+/// The body of an `@expr`, eg. `{if: {cond: {gt: [{path: ["value", "age"]}, {const: 17}]}, then: {const: "adult"}, else: {const: "minor"}}}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExprBody {
+  If {
+    cond: Box<ExprBody>,
+    then: Box<ExprBody>,
+    #[serde(rename = "else")]
+    els: Box<ExprBody>,
+  },
+  And(Vec<ExprBody>),
+  Or(Vec<ExprBody>),
+  Not(Box<ExprBody>),
+  Eq(Box<ExprBody>, Box<ExprBody>),
+  Gt(Box<ExprBody>, Box<ExprBody>),
+  Gte(Box<ExprBody>, Box<ExprBody>),
+  Lt(Box<ExprBody>, Box<ExprBody>),
+  Lte(Box<ExprBody>, Box<ExprBody>),
+  Contains(Box<ExprBody>, Box<ExprBody>),
+  IsNull(Box<ExprBody>),
+  Add(Box<ExprBody>, Box<ExprBody>),
+  Sub(Box<ExprBody>, Box<ExprBody>),
+  Mul(Box<ExprBody>, Box<ExprBody>),
+  Div(Box<ExprBody>, Box<ExprBody>),
+  Concat(Vec<ExprBody>),
+  Object(BTreeMap<String, ExprBody>),
+  List(Vec<ExprBody>),
+  /// A path rooted at `value`, `args`, `vars` or `headers`, like in mustache templates.
+  Path(Vec<String>),
+  Const(Value),
+}
+
 impl Config {
   pub fn from_json(json: &str) -> Result<Self> {
     Ok(serde_json::from_str(json)?)
@@ -348,6 +559,18 @@ impl Config {
     super::n_plus_one::n_plus_one(self)
   }
 
+  /// Resolves the relative paths in the config, like the modules of `@wasm`, against the directory of its file.
+  fn resolve_paths(mut self, dir: &Path) -> Self {
+    for type_ in self.graphql.types.values_mut() {
+      for field in type_.fields.values_mut() {
+        if let Some(wasm) = &mut field.wasm {
+          wasm.module = dir.join(&wasm.module).to_string_lossy().to_string();
+        }
+      }
+    }
+    self
+  }
+
   pub async fn from_file_paths(file_paths: std::slice::Iter<'_, String>) -> Result<Config> {
     let mut config = Config::default();
     let futures: Vec<_> = file_paths
@@ -358,7 +581,8 @@ impl Config {
         f.read_to_end(&mut buffer).await?;
 
         let server_sdl = String::from_utf8(buffer)?;
-        Config::from_source(source, &server_sdl)
+        let dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+        Ok(Config::from_source(source, &server_sdl)?.resolve_paths(dir))
       })
       .collect();
 
@@ -381,13 +605,64 @@ mod tests {
   fn test_field_has_or_not_batch_resolver() {
     let f1 = Field { ..Default::default() };
 
-    let f2 =
-      Field { http: Some(Http { group_by: vec!["id".to_string()], ..Default::default() }), ..Default::default() };
+    let f2 = Field {
+      http: Some(Http { group_by: vec!["id".to_string()].into(), ..Default::default() }),
+      ..Default::default()
+    };
 
-    let f3 = Field { http: Some(Http { group_by: vec![], ..Default::default() }), ..Default::default() };
+    let f3 = Field { http: Some(Http { group_by: vec![].into(), ..Default::default() }), ..Default::default() };
 
     assert!(!f1.has_batched_resolver());
     assert!(f2.has_batched_resolver());
     assert!(!f3.has_batched_resolver());
   }
+
+  #[test]
+  fn test_resolve_paths() {
+    let config = Config::from_sdl(
+      r#"
+      schema {
+        query: Query
+      }
+
+      type Query {
+        a: Int @wasm(module: "plugins/a.wasm", function: "a")
+        b: Int @wasm(module: "/plugins/b.wasm", function: "b")
+      }
+      "#,
+    )
+    .to_result()
+    .unwrap()
+    .resolve_paths(Path::new("examples"));
+    let modules = config.graphql.types["Query"]
+      .fields
+      .values()
+      .map(|field| field.wasm.as_ref().unwrap().module.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(modules, vec!["examples/plugins/a.wasm", "/plugins/b.wasm"]);
+  }
+
+  #[test]
+  fn test_expr_body_from_json() {
+    let body: ExprBody = serde_json::from_value(serde_json::json!({
+      "if": {
+        "cond": {"gt": [{"path": ["args", "age"]}, {"const": 17}]},
+        "then": {"const": "adult"},
+        "else": {"const": "minor"}
+      }
+    }))
+    .unwrap();
+
+    assert_eq!(
+      body,
+      ExprBody::If {
+        cond: Box::new(ExprBody::Gt(
+          Box::new(ExprBody::Path(vec!["args".to_string(), "age".to_string()])),
+          Box::new(ExprBody::Const(serde_json::json!(17)))
+        )),
+        then: Box::new(ExprBody::Const(serde_json::json!("adult"))),
+        els: Box::new(ExprBody::Const(serde_json::json!("minor"))),
+      }
+    );
+  }
 }
@@ -76,6 +76,7 @@ fn pos_name_to_string(pos: &Positioned<Name>) -> String {
 fn to_types(type_definitions: &Vec<&Positioned<TypeDefinition>>) -> Valid<BTreeMap<String, config::Type>, String> {
   Valid::from_iter(type_definitions, |type_definition| {
     let type_name = pos_name_to_string(&type_definition.node.name);
+    let directives = &type_definition.node.directives;
     match type_definition.node.kind.clone() {
       TypeKind::Object(object_type) => to_object_type(
         &object_type.fields,
@@ -91,12 +92,18 @@ fn to_types(type_definitions: &Vec<&Positioned<TypeDefinition>>) -> Valid<BTreeM
         &interface_type.implements,
       )
       .some(),
-      TypeKind::Enum(enum_type) => Valid::succeed(Some(to_enum(enum_type))),
+      TypeKind::Enum(enum_type) => to_enum(enum_type).some(),
       TypeKind::InputObject(input_object_type) => to_input_object(input_object_type).some(),
       TypeKind::Union(_) => Valid::none(),
       TypeKind::Scalar => Valid::succeed(Some(to_scalar_type())),
     }
-    .map(|option| (type_name, option))
+    .zip(to_omit(directives))
+    .zip(to_added_fields(directives))
+    .map(|((option, omit), added_fields)| {
+      let modify = to_modify(directives);
+      let option = option.map(|type_| config::Type { modify, omit, added_fields, ..type_ });
+      (type_name, option)
+    })
   })
   .map(|vec| {
     BTreeMap::from_iter(
@@ -136,13 +143,26 @@ fn to_object_type(
     config::Type { fields, doc, interface, implements, ..Default::default() }
   })
 }
-fn to_enum(enum_type: EnumType) -> config::Type {
+fn to_enum(enum_type: EnumType) -> Valid<config::Type, String> {
   let variants = enum_type
     .values
     .iter()
     .map(|value| value.node.value.to_string())
     .collect();
-  config::Type { variants: Some(variants), ..Default::default() }
+  let variant_modify = enum_type
+    .values
+    .iter()
+    .filter_map(|value| Some((value.node.value.to_string(), to_modify(&value.node.directives)?)))
+    .collect();
+  Valid::from_iter(enum_type.values.iter(), |value| {
+    to_alias(&value.node.directives).map(|alias| alias.map(|alias| (value.node.value.to_string(), alias)))
+  })
+  .map(|variant_alias| config::Type {
+    variants: Some(variants),
+    variant_modify,
+    variant_alias: variant_alias.into_iter().flatten().collect(),
+    ..Default::default()
+  })
 }
 fn to_input_object(input_object_type: InputObjectType) -> Valid<config::Type, String> {
   to_input_object_fields(&input_object_type.fields).map(|fields| config::Type { fields, ..Default::default() })
@@ -201,23 +221,36 @@ fn to_common_field(
   let doc = description.as_ref().map(|pos| pos.node.clone());
   let modify = to_modify(directives);
   let inline = to_inline(directives);
-  to_http(directives).map(|http| {
-    let unsafe_operation = to_unsafe_operation(directives);
-    let const_field = to_const_field(directives);
-    config::Field {
-      type_of,
-      list,
-      required: !nullable,
-      list_type_required,
-      args,
-      doc,
-      modify,
-      inline,
-      http,
-      unsafe_operation,
-      const_field,
-    }
-  })
+  to_http(directives)
+    .zip(to_expr(directives))
+    .zip(to_pipe(directives))
+    .zip(to_call(directives))
+    .zip(to_wasm(directives))
+    .zip(to_rust(directives))
+    .zip(to_transform(directives))
+    .map(|((((((http, expr), pipe), call), wasm), rust), transform)| {
+      let unsafe_operation = to_unsafe_operation(directives);
+      let const_field = to_const_field(directives);
+      config::Field {
+        type_of,
+        list,
+        required: !nullable,
+        list_type_required,
+        args,
+        doc,
+        modify,
+        inline,
+        transform,
+        http,
+        unsafe_operation,
+        const_field,
+        expr,
+        pipe,
+        call,
+        wasm,
+        rust,
+      }
+    })
 }
 fn to_unsafe_operation(directives: &[Positioned<ConstDirective>]) -> Option<config::Unsafe> {
   directives.iter().find_map(|directive| {
@@ -271,6 +304,30 @@ fn to_modify(directives: &[Positioned<ConstDirective>]) -> Option<config::Modify
     }
   })
 }
+fn to_alias(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Alias>, String> {
+  for directive in directives {
+    if directive.node.name.node == "alias" {
+      return config::Alias::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_omit(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Omit>, String> {
+  for directive in directives {
+    if directive.node.name.node == "omit" {
+      return config::Omit::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_added_fields(directives: &[Positioned<ConstDirective>]) -> Valid<Vec<config::AddField>, String> {
+  Valid::from_iter(
+    directives
+      .iter()
+      .filter(|directive| directive.node.name.node == "addField"),
+    |directive| config::AddField::from_directive(&directive.node),
+  )
+}
 fn to_inline(directives: &[Positioned<ConstDirective>]) -> Option<config::InlineType> {
   directives.iter().find_map(|directive| {
     if directive.node.name.node == "inline" {
@@ -305,6 +362,55 @@ fn to_const_field(directives: &[Positioned<ConstDirective>]) -> Option<config::C
     }
   })
 }
+fn to_expr(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Expr>, String> {
+  for directive in directives {
+    if directive.node.name.node == "expr" {
+      return config::Expr::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_pipe(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Pipe>, String> {
+  for directive in directives {
+    if directive.node.name.node == "pipe" {
+      return config::Pipe::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_call(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Call>, String> {
+  for directive in directives {
+    if directive.node.name.node == "call" {
+      return config::Call::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_wasm(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Wasm>, String> {
+  for directive in directives {
+    if directive.node.name.node == "wasm" {
+      return config::Wasm::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+fn to_rust(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Rust>, String> {
+  for directive in directives {
+    if directive.node.name.node == "rust" {
+      return config::Rust::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
+
+fn to_transform(directives: &[Positioned<ConstDirective>]) -> Valid<Option<config::Transform>, String> {
+  for directive in directives {
+    if directive.node.name.node == "transform" {
+      return config::Transform::from_directive(&directive.node).map(Some);
+    }
+  }
+  Valid::succeed(None)
+}
 
 trait HasName {
   fn name(&self) -> &Positioned<Name>;
@@ -1,15 +1,22 @@
 use serde::{Deserialize, Serialize};
 
 use crate::config::is_default;
+
+/// A single component of a batching key.
+/// `path` locates the key's value in the upstream response, and `query_param` is the
+/// query param (or body field, when using a `batchKey`) that carries it upstream.
 #[derive(Clone, Debug, Eq, Serialize, Deserialize, PartialEq)]
-pub struct GroupBy {
+#[serde(rename_all = "camelCase")]
+pub struct GroupByKey {
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub path: Vec<String>,
   #[serde(default, skip_serializing_if = "is_default")]
-  path: Vec<String>,
+  pub query_param: Option<String>,
 }
 
-impl GroupBy {
+impl GroupByKey {
   pub fn new(path: Vec<String>) -> Self {
-    Self { path }
+    Self { path, query_param: None }
   }
 
   pub fn path(&self) -> Vec<String> {
@@ -19,8 +26,171 @@ impl GroupBy {
     self.path.clone()
   }
 
-  pub fn key(&self) -> &str {
-    self.path.last().map(|a| a.as_str()).unwrap_or(ID)
+  /// Defaults to the last segment of the path.
+  pub fn query_param(&self) -> &str {
+    self
+      .query_param
+      .as_deref()
+      .or(self.path.last().map(|a| a.as_str()))
+      .unwrap_or(ID)
+  }
+}
+
+/// The `groupBy` setting of `@http`, which can either be a path eg. `["id"]`,
+/// a single key eg. `{path: ["owner", "id"], queryParam: "ownerIds"}`,
+/// or a composite key eg. `[{path: ["tenantId"]}, {path: ["userId"]}]`.
+#[derive(Clone, Debug, Eq, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum GroupByKeys {
+  Path(Vec<String>),
+  Key(GroupByKey),
+  Composite(Vec<GroupByKey>),
+}
+
+impl GroupByKeys {
+  pub fn is_empty(&self) -> bool {
+    match self {
+      GroupByKeys::Path(path) => path.is_empty(),
+      GroupByKeys::Key(_) => false,
+      GroupByKeys::Composite(keys) => keys.is_empty(),
+    }
+  }
+
+  pub fn to_keys(&self) -> Vec<GroupByKey> {
+    match self {
+      GroupByKeys::Path(path) => vec![GroupByKey::new(path.clone())],
+      GroupByKeys::Key(key) => vec![key.clone()],
+      GroupByKeys::Composite(keys) => keys.clone(),
+    }
+  }
+}
+
+impl Default for GroupByKeys {
+  fn default() -> Self {
+    GroupByKeys::Path(Vec::new())
+  }
+}
+
+impl From<Vec<String>> for GroupByKeys {
+  fn from(path: Vec<String>) -> Self {
+    GroupByKeys::Path(path)
+  }
+}
+
+/// How the ids of batched GET requests are encoded into the merged request.
+#[derive(Clone, Debug, Default, Eq, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum BatchEncoding {
+  /// Repeats the query param for each id eg. `/users?id=1&id=2`
+  #[default]
+  Repeat,
+  /// Joins the ids into a single query param eg. `/users?ids=1,2`
+  Comma,
+  /// Joins the ids into the last path segment eg. `/users/1,2`
+  Path,
+}
+
+/// What a batched field resolves to when its key is missing from the upstream response.
+#[derive(Clone, Debug, Default, Eq, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OnMissingKey {
+  #[default]
+  Null,
+  Error,
+}
+
+#[derive(Clone, Debug, Eq, Serialize, Deserialize, PartialEq)]
+pub struct GroupBy {
+  #[serde(default, skip_serializing_if = "is_default")]
+  keys: Vec<GroupByKey>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  batch_key: Option<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  encoding: BatchEncoding,
+  #[serde(default, skip_serializing_if = "is_default")]
+  on_missing_key: OnMissingKey,
+  #[serde(default, skip_serializing_if = "is_default")]
+  item_path: Option<Vec<String>>,
+}
+
+impl GroupBy {
+  pub fn new(path: Vec<String>) -> Self {
+    Self::from_keys(vec![GroupByKey::new(path)])
+  }
+
+  pub fn from_keys(keys: Vec<GroupByKey>) -> Self {
+    Self {
+      keys,
+      batch_key: None,
+      encoding: BatchEncoding::default(),
+      on_missing_key: OnMissingKey::default(),
+      item_path: None,
+    }
+  }
+
+  /// Batches requests by merging their bodies into an array under `batch_key`,
+  /// instead of merging their query params.
+  pub fn with_batch_key(mut self, batch_key: Option<String>) -> Self {
+    self.batch_key = batch_key;
+    self
+  }
+
+  pub fn batch_key(&self) -> Option<&str> {
+    self.batch_key.as_deref()
+  }
+
+  pub fn with_encoding(mut self, encoding: BatchEncoding) -> Self {
+    self.encoding = encoding;
+    self
+  }
+
+  pub fn encoding(&self) -> &BatchEncoding {
+    &self.encoding
+  }
+
+  pub fn with_on_missing_key(mut self, on_missing_key: OnMissingKey) -> Self {
+    self.on_missing_key = on_missing_key;
+    self
+  }
+
+  pub fn on_missing_key(&self) -> &OnMissingKey {
+    &self.on_missing_key
+  }
+
+  /// Matches the keys in the items at `item_path`, instead of the ones found from the key paths.
+  pub fn with_item_path(mut self, item_path: Option<Vec<String>>) -> Self {
+    self.item_path = item_path;
+    self
+  }
+
+  pub fn keys(&self) -> &[GroupByKey] {
+    &self.keys
+  }
+
+  pub fn query_params(&self) -> Vec<&str> {
+    self.keys.iter().map(|key| key.query_param()).collect()
+  }
+
+  /// Splits the key paths into the path to the items being grouped and the path of each key relative to an
+  /// item. Unless it's set explicitly, the items are the parent of a single key, and the longest common
+  /// parent of composite keys.
+  pub fn item_path(&self) -> (Vec<String>, Vec<Vec<String>>) {
+    let paths = self.keys.iter().map(|key| key.path()).collect::<Vec<_>>();
+    let prefix_len = match &self.item_path {
+      Some(item_path) => item_path.len(),
+      None => {
+        let max_len = paths.iter().map(|path| path.len() - 1).min().unwrap_or(0);
+        (0..max_len)
+          .take_while(|i| paths.iter().all(|path| path[*i] == paths[0][*i]))
+          .count()
+      }
+    };
+    let prefix = paths
+      .first()
+      .map(|path| path[..prefix_len].to_vec())
+      .unwrap_or_default();
+    let relative_paths = paths.into_iter().map(|path| path[prefix_len..].to_vec()).collect();
+    (prefix, relative_paths)
   }
 }
 
@@ -28,6 +198,68 @@ const ID: &str = "id";
 
 impl Default for GroupBy {
   fn default() -> Self {
-    Self { path: vec![ID.to_string()] }
+    Self::new(vec![ID.to_string()])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn path(path: &[&str]) -> Vec<String> {
+    path.iter().map(|a| a.to_string()).collect()
+  }
+
+  #[test]
+  fn test_query_param_defaults_to_last_segment() {
+    assert_eq!(GroupByKey::new(path(&["user", "id"])).query_param(), "id");
+    assert_eq!(GroupByKey::new(vec![]).query_param(), "id");
+    let key = GroupByKey { path: path(&["owner", "id"]), query_param: Some("ownerIds".to_string()) };
+    assert_eq!(key.query_param(), "ownerIds");
+  }
+
+  #[test]
+  fn test_item_path_single_key() {
+    let group_by = GroupBy::new(path(&["data", "items", "owner", "id"]));
+    let (prefix, relative) = group_by.item_path();
+    assert_eq!(prefix, path(&["data", "items", "owner"]));
+    assert_eq!(relative, vec![path(&["id"])]);
+  }
+
+  #[test]
+  fn test_item_path_single_nested_key() {
+    let group_by = GroupBy::new(path(&["data", "items", "owner", "id"])).with_item_path(Some(path(&["data", "items"])));
+    let (prefix, relative) = group_by.item_path();
+    assert_eq!(prefix, path(&["data", "items"]));
+    assert_eq!(relative, vec![path(&["owner", "id"])]);
+  }
+
+  #[test]
+  fn test_item_path_composite_key() {
+    let group_by = GroupBy::from_keys(vec![
+      GroupByKey::new(path(&["data", "tenantId"])),
+      GroupByKey::new(path(&["data", "owner", "id"])),
+    ]);
+    let (prefix, relative) = group_by.item_path();
+    assert_eq!(prefix, path(&["data"]));
+    assert_eq!(relative, vec![path(&["tenantId"]), path(&["owner", "id"])]);
+  }
+
+  #[test]
+  fn test_group_by_keys_from_config() {
+    let keys: GroupByKeys = serde_json::from_str(r#"["id"]"#).unwrap();
+    assert_eq!(keys, GroupByKeys::Path(path(&["id"])));
+
+    let keys: GroupByKeys = serde_json::from_str(r#"{"path": ["owner", "id"], "queryParam": "ownerIds"}"#).unwrap();
+    assert_eq!(
+      keys.to_keys(),
+      vec![GroupByKey { path: path(&["owner", "id"]), query_param: Some("ownerIds".to_string()) }]
+    );
+
+    let keys: GroupByKeys = serde_json::from_str(r#"[{"path": ["tenantId"]}, {"path": ["userId"]}]"#).unwrap();
+    assert_eq!(
+      keys.to_keys(),
+      vec![GroupByKey::new(path(&["tenantId"])), GroupByKey::new(path(&["userId"]))]
+    );
   }
 }
@@ -64,7 +64,16 @@ fn config_document(config: &Config) -> ServiceDocument {
         values: variants
           .iter()
           .map(|value| {
-            pos(EnumValueDefinition { description: None, value: pos(Name::new(value.clone())), directives: Vec::new() })
+            let modify = type_def
+              .variant_modify
+              .get(value)
+              .map(|modify| pos(modify.to_directive("modify".to_string())));
+            let alias = type_def
+              .variant_alias
+              .get(value)
+              .map(|alias| pos(alias.to_directive("alias".to_string())));
+            let directives = modify.into_iter().chain(alias).collect();
+            pos(EnumValueDefinition { description: None, value: pos(Name::new(value.clone())), directives })
           })
           .collect(),
       })
@@ -162,7 +171,7 @@ fn config_document(config: &Config) -> ServiceDocument {
       extend: false,
       description: None,
       name: pos(Name::new(type_name.clone())),
-      directives: Vec::new(),
+      directives: get_type_directives(type_def),
       kind,
     })));
   }
@@ -181,6 +190,20 @@ fn config_document(config: &Config) -> ServiceDocument {
   ServiceDocument { definitions }
 }
 
+fn get_type_directives(type_def: &crate::config::Type) -> Vec<Positioned<ConstDirective>> {
+  let mut directives = Vec::new();
+  if let Some(modify) = &type_def.modify {
+    directives.push(pos(modify.to_directive("modify".to_string())));
+  }
+  if let Some(omit) = &type_def.omit {
+    directives.push(pos(omit.to_directive("omit".to_string())));
+  }
+  for added_field in type_def.added_fields.iter() {
+    directives.push(pos(added_field.to_directive("addField".to_string())));
+  }
+  directives
+}
+
 fn get_directives(field: &crate::config::Field) -> Vec<Positioned<ConstDirective>> {
   let mut directives = Vec::new();
   if let Some(http) = field.clone().http {
@@ -195,10 +218,34 @@ fn get_directives(field: &crate::config::Field) -> Vec<Positioned<ConstDirective
     let us_dir = const_field.to_directive("const".to_string());
     directives.push(pos(us_dir));
   }
+  if let Some(expr) = field.clone().expr {
+    let expr_dir = expr.to_directive("expr".to_string());
+    directives.push(pos(expr_dir));
+  }
+  if let Some(pipe) = field.clone().pipe {
+    let pipe_dir = pipe.to_directive("pipe".to_string());
+    directives.push(pos(pipe_dir));
+  }
+  if let Some(call) = field.clone().call {
+    let call_dir = call.to_directive("call".to_string());
+    directives.push(pos(call_dir));
+  }
+  if let Some(wasm) = field.clone().wasm {
+    let wasm_dir = wasm.to_directive("wasm".to_string());
+    directives.push(pos(wasm_dir));
+  }
+  if let Some(rust) = field.clone().rust {
+    let rust_dir = rust.to_directive("rust".to_string());
+    directives.push(pos(rust_dir));
+  }
   if let Some(inline) = field.clone().inline {
     let il_dir = inline.to_directive("inline".to_string());
     directives.push(pos(il_dir));
   }
+  if let Some(transform) = field.clone().transform {
+    let dir = transform.to_directive("transform".to_string());
+    directives.push(pos(dir));
+  }
   if let Some(modify) = field.clone().modify {
     let dir = modify.to_directive("modify".to_string());
     directives.push(pos(dir));
@@ -6,11 +6,13 @@ mod key_values;
 mod n_plus_one;
 mod server;
 mod source;
+mod transform;
 
 pub use config::*;
 pub use key_values::*;
 pub use server::*;
 pub use source::*;
+pub use transform::*;
 
 fn is_default<T: Default + Eq>(val: &T) -> bool {
   *val == T::default()
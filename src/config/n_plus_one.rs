@@ -97,7 +97,7 @@ mod tests {
           Field::default()
             .type_of("F2".to_string())
             .to_list()
-            .http(Http { group_by: vec!["id".into()], ..Default::default() }),
+            .http(Http { group_by: vec!["id".to_string()].into(), ..Default::default() }),
         )]),
       ),
       (
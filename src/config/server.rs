@@ -14,10 +14,17 @@ pub struct Server {
   pub enable_introspection: Option<bool>,
   pub enable_query_validation: Option<bool>,
   pub enable_response_validation: Option<bool>,
+  /// Environment variables that templates can read with `{{env.NAME}}`.
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub env: BTreeSet<String>,
   pub global_response_timeout: Option<i64>,
   #[serde(skip_serializing_if = "is_default")]
   pub hostname: Option<String>,
   pub port: Option<u16>,
+  #[serde(skip_serializing_if = "is_default")]
+  pub script_timeout: Option<u64>,
+  #[serde(skip_serializing_if = "is_default")]
+  pub script_workers: Option<usize>,
   #[serde(default, skip_serializing_if = "is_default")]
   pub vars: KeyValues,
   #[serde(skip_serializing_if = "is_default", default)]
@@ -47,6 +54,16 @@ impl Server {
   pub fn get_global_response_timeout(&self) -> i64 {
     self.global_response_timeout.unwrap_or(0)
   }
+  /// Maximum time in milliseconds a single JS evaluation may run for.
+  pub fn get_script_timeout(&self) -> u64 {
+    self.script_timeout.unwrap_or(5)
+  }
+  /// Number of JS isolates kept warm, each on its own thread.
+  pub fn get_script_workers(&self) -> usize {
+    self
+      .script_workers
+      .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+  }
   pub fn get_port(&self) -> u16 {
     self.port.unwrap_or(8000)
   }
@@ -67,6 +84,10 @@ impl Server {
     self.hostname.clone().unwrap_or("127.0.0.1".to_string())
   }
 
+  pub fn get_env(&self) -> BTreeSet<String> {
+    self.env.clone()
+  }
+
   pub fn get_vars(&self) -> BTreeMap<String, String> {
     self.vars.clone().0
   }
@@ -82,8 +103,11 @@ impl Server {
     self.enable_introspection = other.enable_introspection.or(self.enable_introspection);
     self.enable_query_validation = other.enable_query_validation.or(self.enable_query_validation);
     self.enable_response_validation = other.enable_response_validation.or(self.enable_response_validation);
+    self.env.extend(other.env);
     self.global_response_timeout = other.global_response_timeout.or(self.global_response_timeout);
     self.port = other.port.or(self.port);
+    self.script_timeout = other.script_timeout.or(self.script_timeout);
+    self.script_workers = other.script_workers.or(self.script_workers);
     self.hostname = other.hostname.or(self.hostname);
     let mut vars = self.vars.0.clone();
     vars.extend(other.vars.0);
@@ -100,6 +124,23 @@ pub struct Proxy {
   pub url: String,
 }
 
+/// JS expressions evaluated around every upstream call, with the request
+/// (and for `on_response`, the response) bound to `ctx`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamScripts {
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub on_request: Option<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub on_response: Option<String>,
+}
+
+impl UpstreamScripts {
+  pub fn scripts(&self) -> Vec<String> {
+    self.on_request.iter().chain(self.on_response.iter()).cloned().collect()
+  }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Setters, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct Upstream {
@@ -132,6 +173,8 @@ pub struct Upstream {
   pub enable_http_cache: Option<bool>,
   #[serde(skip_serializing_if = "is_default")]
   pub batch: Option<Batch>,
+  #[serde(skip_serializing_if = "is_default")]
+  pub scripts: Option<UpstreamScripts>,
 }
 
 impl Upstream {
@@ -190,6 +233,7 @@ impl Upstream {
     self.tcp_keep_alive = other.tcp_keep_alive.or(self.tcp_keep_alive);
     self.timeout = other.timeout.or(self.timeout);
     self.user_agent = other.user_agent.or(self.user_agent);
+    self.scripts = other.scripts.or(self.scripts);
     self.batch = other.batch.map(|other| {
       let mut batch = self.batch.unwrap_or_default();
       batch.max_size = other.max_size;
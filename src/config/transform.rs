@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use async_graphql::{Name, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::config::is_default;
+use crate::json::JsonLike;
+
+/// Reshapes the output of a field's resolver before the field is resolved, eg.
+/// `@transform(path: ["data", "items"], each: ["node"], rename: {user_name: "name"})`.
+/// `path` selects a value from the output, and when that value is a list, the other steps apply to each of its
+/// items: `each` selects a value from the item, `rename` renames its keys and `wrap` nests it under a key.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Transform {
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub path: Vec<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub each: Vec<String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub rename: BTreeMap<String, String>,
+  #[serde(default, skip_serializing_if = "is_default")]
+  pub wrap: Option<String>,
+}
+
+impl Transform {
+  pub fn apply(&self, value: Value) -> Value {
+    let value = select(value, &self.path);
+    match value {
+      Value::List(items) => Value::List(items.into_iter().map(|item| self.apply_item(item)).collect()),
+      value => self.apply_item(value),
+    }
+  }
+
+  fn apply_item(&self, value: Value) -> Value {
+    let value = match select(value, &self.each) {
+      Value::Object(fields) if !self.rename.is_empty() => Value::Object(
+        fields
+          .into_iter()
+          .map(|(name, value)| match self.rename.get(name.as_str()) {
+            Some(new_name) => (Name::new(new_name), value),
+            None => (name, value),
+          })
+          .collect(),
+      ),
+      value => value,
+    };
+    match &self.wrap {
+      Some(name) => Value::Object([(Name::new(name), value)].into_iter().collect()),
+      None => value,
+    }
+  }
+}
+
+fn select(value: Value, path: &[String]) -> Value {
+  if path.is_empty() {
+    value
+  } else {
+    value.get_path(path).cloned().unwrap_or_default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use async_graphql::Value;
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::Transform;
+
+  fn apply(transform: Transform, value: serde_json::Value) -> serde_json::Value {
+    transform.apply(Value::from_json(value).unwrap()).into_json().unwrap()
+  }
+
+  #[test]
+  fn test_path() {
+    let transform = Transform { path: vec!["data".to_string(), "items".to_string()], ..Default::default() };
+    let value = apply(transform, json!({"data": {"items": [{"id": 1}]}}));
+    assert_eq!(value, json!([{"id": 1}]));
+  }
+
+  #[test]
+  fn test_missing_path() {
+    let transform = Transform { path: vec!["data".to_string()], ..Default::default() };
+    assert_eq!(apply(transform, json!({"items": []})), json!(null));
+  }
+
+  #[test]
+  fn test_each_and_rename() {
+    let transform = Transform {
+      path: vec!["edges".to_string()],
+      each: vec!["node".to_string()],
+      rename: BTreeMap::from([("user_name".to_string(), "name".to_string())]),
+      ..Default::default()
+    };
+    let value = apply(
+      transform,
+      json!({"edges": [{"node": {"id": 1, "user_name": "John"}}, {"node": {"id": 2, "user_name": "Jane"}}]}),
+    );
+    assert_eq!(value, json!([{"id": 1, "name": "John"}, {"id": 2, "name": "Jane"}]));
+  }
+
+  #[test]
+  fn test_wrap() {
+    let transform = Transform { wrap: Some("name".to_string()), ..Default::default() };
+    assert_eq!(
+      apply(transform.clone(), json!(["a", "b"])),
+      json!([{"name": "a"}, {"name": "b"}])
+    );
+    assert_eq!(apply(transform, json!("a")), json!({"name": "a"}));
+  }
+}
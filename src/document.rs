@@ -59,7 +59,15 @@ fn const_directive_to_sdl(directive: &ConstDirective) -> DirectiveDefinition {
     locations: vec![],
   }
 }
+/// Prints each directive followed by a space.
+fn print_directives(directives: &[Positioned<ConstDirective>]) -> String {
+  directives
+    .iter()
+    .map(|d| format!("{} ", print_directive(&const_directive_to_sdl(&d.node))))
+    .collect()
+}
 fn print_type_def(type_def: &TypeDefinition) -> String {
+  let directives = print_directives(&type_def.directives);
   match &type_def.kind {
     TypeKind::Scalar => {
       format!("scalar {}\n", type_def.name.node)
@@ -103,9 +111,10 @@ fn print_type_def(type_def: &TypeDefinition) -> String {
         String::new()
       };
       format!(
-        "interface {} {}{{\n{}\n}}\n",
+        "interface {} {}{}{{\n{}\n}}\n",
         type_def.name.node,
         implements,
+        directives,
         interface
           .fields
           .iter()
@@ -129,9 +138,10 @@ fn print_type_def(type_def: &TypeDefinition) -> String {
         String::new()
       };
       format!(
-        "type {} {}{{\n{}\n}}\n",
+        "type {} {}{}{{\n{}\n}}\n",
         type_def.name.node,
         implements,
+        directives,
         object
           .fields
           .iter()
@@ -141,11 +151,16 @@ fn print_type_def(type_def: &TypeDefinition) -> String {
       )
     }
     TypeKind::Enum(en) => format!(
-      "enum {} {{\n{}\n}}\n",
+      "enum {} {}{{\n{}\n}}\n",
       type_def.name.node,
+      directives,
       en.values
         .iter()
-        .map(|v| format!("  {}", v.node.value))
+        .map(
+          |v| format!("  {} {}", v.node.value, print_directives(&v.node.directives))
+            .trim_end()
+            .to_string()
+        )
         .collect::<Vec<String>>()
         .join("\n")
     ),
@@ -3,6 +3,7 @@
 use derive_setters::Setters;
 use hyper::HeaderMap;
 
+use crate::config::{Encoding, ResponseFormat};
 use crate::http::Method;
 use crate::json::JsonSchema;
 
@@ -14,7 +15,9 @@ pub struct Endpoint {
   pub input: JsonSchema,
   pub output: JsonSchema,
   pub headers: HeaderMap,
-  pub body: Option<String>,
+  pub body: Option<serde_json::Value>,
+  pub encoding: Encoding,
+  pub response_format: Option<ResponseFormat>,
   pub description: Option<String>,
 }
 
@@ -28,6 +31,8 @@ impl Endpoint {
       output: Default::default(),
       headers: Default::default(),
       body: Default::default(),
+      encoding: Default::default(),
+      response_format: Default::default(),
       description: Default::default(),
     }
   }
@@ -0,0 +1,45 @@
+use std::io::Read;
+
+use async_graphql::UploadValue;
+
+use crate::lambda::{EvaluationContext, ResolverContextLike};
+
+/// A file sent along with a GraphQL multipart request, read into memory so it can be forwarded upstream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+  pub filename: String,
+  pub content_type: Option<String>,
+  pub content: Vec<u8>,
+}
+
+impl Upload {
+  /// The upload a variable points to. Variables holding files are replaced with `#__graphql_file__:<index>`.
+  pub fn find<'a>(uploads: &'a [Upload], value: &str) -> Option<&'a Upload> {
+    let index = value.strip_prefix("#__graphql_file__:")?.parse::<usize>().ok()?;
+    uploads.get(index)
+  }
+}
+
+impl TryFrom<UploadValue> for Upload {
+  type Error = std::io::Error;
+
+  fn try_from(value: UploadValue) -> Result<Self, Self::Error> {
+    let filename = value.filename.clone();
+    let content_type = value.content_type.clone();
+    let mut content = Vec::new();
+    value.into_read().read_to_end(&mut content)?;
+    Ok(Upload { filename, content_type, content })
+  }
+}
+
+pub trait HasUploads {
+  fn uploads(&self) -> &[Upload] {
+    &[]
+  }
+}
+
+impl<'a, Ctx: ResolverContextLike<'a> + ?Sized> HasUploads for EvaluationContext<'a, Ctx> {
+  fn uploads(&self) -> &[Upload] {
+    &self.req_ctx.uploads
+  }
+}
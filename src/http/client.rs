@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use http_cache_reqwest::{Cache, CacheMode, HttpCache, HttpCacheOptions, MokaManager};
@@ -7,11 +8,20 @@ use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use super::Response;
 use crate::config::{self, Upstream};
 
+/// Sends the upstream requests. Implementations can wrap another client to
+/// sign, record or fail requests, or replace the network entirely in tests.
 #[async_trait::async_trait]
-pub trait HttpClient {
+pub trait HttpClient: Send + Sync {
   async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response>;
 }
 
+#[async_trait::async_trait]
+impl<C: HttpClient + ?Sized> HttpClient for Arc<C> {
+  async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+    self.as_ref().execute(req).await
+  }
+}
+
 #[async_trait::async_trait]
 impl HttpClient for DefaultHttpClient {
   async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
@@ -33,6 +43,12 @@ impl Default for DefaultHttpClient {
   }
 }
 
+impl From<ClientWithMiddleware> for DefaultHttpClient {
+  fn from(client: ClientWithMiddleware) -> Self {
+    DefaultHttpClient { client }
+  }
+}
+
 impl DefaultHttpClient {
   pub fn new(upstream: Upstream) -> Self {
     let mut builder = Client::builder()
@@ -60,7 +76,7 @@ impl DefaultHttpClient {
       }))
     }
 
-    DefaultHttpClient { client: client.build() }
+    DefaultHttpClient::from(client.build())
   }
 
   pub async fn execute(&self, request: reqwest::Request) -> reqwest_middleware::Result<Response> {
@@ -70,3 +86,18 @@ impl DefaultHttpClient {
     Ok(response)
   }
 }
+
+/// Answers every request with the response the function builds for it, for tests that don't reach the network.
+#[cfg(test)]
+pub struct MockHttpClient<F>(pub F);
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl<F> HttpClient for MockHttpClient<F>
+where
+  F: Fn(&reqwest::Request) -> anyhow::Result<Response> + Send + Sync,
+{
+  async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+    (self.0)(&req)
+  }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -6,26 +7,25 @@ use async_graphql::async_trait;
 use async_graphql::dataloader::{DataLoader, Loader, NoCache};
 use async_graphql::futures_util::future::join_all;
 use async_graphql_value::ConstValue;
+use url::form_urlencoded;
 
-use crate::config::group_by::GroupBy;
-use crate::config::Batch;
+use crate::config::group_by::{BatchEncoding, GroupBy, OnMissingKey};
+use crate::config::{Batch, ResponseFormat};
 use crate::http::{DataLoaderRequest, HttpClient, Response};
-use crate::json::JsonLike;
-
-#[derive(Default, Clone, Debug)]
-pub struct HttpDataLoader<C>
-where
-  C: HttpClient + Send + Sync + 'static + Clone,
-{
-  pub client: C,
+use crate::json::group_by_keys;
+
+#[derive(Clone)]
+pub struct HttpDataLoader {
+  pub client: Arc<dyn HttpClient>,
   pub batched: Option<GroupBy>,
+  pub response_format: Option<ResponseFormat>,
 }
-impl<C: HttpClient + Send + Sync + 'static + Clone> HttpDataLoader<C> {
-  pub fn new(client: C, batched: Option<GroupBy>) -> Self {
-    HttpDataLoader { client, batched }
+impl HttpDataLoader {
+  pub fn new(client: Arc<dyn HttpClient>, batched: Option<GroupBy>, response_format: Option<ResponseFormat>) -> Self {
+    HttpDataLoader { client, batched, response_format }
   }
 
-  pub fn to_data_loader(self, batch: Batch) -> DataLoader<HttpDataLoader<C>, NoCache> {
+  pub fn to_data_loader(self, batch: Batch) -> DataLoader<HttpDataLoader, NoCache> {
     DataLoader::new(self, tokio::spawn)
       .delay(Duration::from_millis(batch.delay as u64))
       .max_batch_size(batch.max_size)
@@ -33,8 +33,9 @@ impl<C: HttpClient + Send + Sync + 'static + Clone> HttpDataLoader<C> {
 }
 
 #[async_trait::async_trait]
-impl<C: HttpClient + Send + Sync + 'static + Clone> Loader<DataLoaderRequest> for HttpDataLoader<C> {
-  type Value = Response;
+impl Loader<DataLoaderRequest> for HttpDataLoader {
+  /// Each key carries its own result, so that a failed upstream call only fails the fields that depend on it.
+  type Value = Result<Response, Arc<anyhow::Error>>;
   type Error = Arc<anyhow::Error>;
 
   async fn load(
@@ -45,42 +46,79 @@ impl<C: HttpClient + Send + Sync + 'static + Clone> Loader<DataLoaderRequest> fo
       let mut keys = keys.to_vec();
       keys.sort_by(|a, b| a.to_request().url().cmp(b.to_request().url()));
 
-      let mut request = keys[0].to_request();
-      let first_url = request.url_mut();
-
-      for key in &keys[1..] {
-        let request = key.to_request();
-        let url = request.url();
-        first_url.query_pairs_mut().extend_pairs(url.query_pairs());
-      }
+      let ids = match (group_by.batch_key(), group_by.encoding()) {
+        (Some(_), _) => body_ids(&keys, &group_by.query_params()),
+        (None, BatchEncoding::Path) => path_ids(&keys),
+        (None, _) => query_param_ids(&keys, &group_by.query_params()),
+      };
 
-      let res = self.client.execute(request).await?;
       #[allow(clippy::mutable_key_type)]
-      let mut hashmap: HashMap<DataLoaderRequest, Response> = HashMap::with_capacity(keys.len());
-      let path = &group_by.path();
-      let body_value = res.body.group_by(path);
-
-      for key in &keys {
-        let req = key.to_request();
-        let query_set: std::collections::HashMap<_, _> = req.url().query_pairs().collect();
-        let id = query_set
-          .get(group_by.key())
-          .ok_or(anyhow::anyhow!("Unable to find key {} in query params", group_by.key()))?;
-        hashmap.insert(
-          key.clone(),
-          res.clone().body(
-            body_value
-              .get(id.as_ref())
-              .and_then(|a| a.first().cloned().cloned())
-              .unwrap_or(ConstValue::Null),
-          ),
-        );
+      let mut hashmap: HashMap<DataLoaderRequest, Self::Value> = HashMap::with_capacity(keys.len());
+
+      // Keys whose id can't be read fail on their own, and are left out of the batch
+      let (keys, ids): (Vec<_>, Vec<_>) = keys
+        .into_iter()
+        .zip(ids)
+        .filter_map(|(key, id)| match id {
+          Ok(id) => Some((key, id)),
+          Err(e) => {
+            hashmap.insert(key, Err(Arc::new(e)));
+            None
+          }
+        })
+        .unzip();
+
+      let batches = split_batches(&keys, &ids, &group_by);
+      let results = join_all(batches.into_iter().map(|(request, range)| async move {
+        let result = match request {
+          Ok(request) => self.client.execute(request).await,
+          Err(e) => Err(e),
+        };
+        (range, result)
+      }))
+      .await;
+
+      let (path, key_paths) = group_by.item_path();
+
+      for (range, result) in results {
+        let res = match result
+          .and_then(Response::error_for_status)
+          .and_then(|res| Ok(res.decode(self.response_format.as_ref())?))
+        {
+          Ok(res) => res,
+          Err(e) => {
+            let e = Arc::new(e);
+            for key in &keys[range] {
+              hashmap.insert(key.clone(), Err(e.clone()));
+            }
+            continue;
+          }
+        };
+        let body_value = group_by_keys(&res.body, &path, &key_paths);
+
+        for (key, id) in keys[range.clone()].iter().zip(&ids[range]) {
+          let value = match body_value.get(id).and_then(|a| a.first()) {
+            Some(value) => Ok(res.clone().body((*value).clone())),
+            None => match group_by.on_missing_key() {
+              OnMissingKey::Null => Ok(res.clone().body(ConstValue::Null)),
+              OnMissingKey::Error => Err(Arc::new(anyhow::anyhow!(
+                "Unable to find key {} in the upstream response",
+                id.join(",")
+              ))),
+            },
+          };
+          hashmap.insert(key.clone(), value);
+        }
       }
 
       Ok(hashmap)
     } else {
       let results = keys.iter().map(|key| async {
-        let result = self.client.execute(key.to_request()).await;
+        let result = self
+          .client
+          .execute(key.to_request())
+          .await
+          .and_then(Response::error_for_status);
         (key.clone(), result)
       });
 
@@ -89,7 +127,7 @@ impl<C: HttpClient + Send + Sync + 'static + Clone> Loader<DataLoaderRequest> fo
       #[allow(clippy::mutable_key_type)]
       let mut hashmap = HashMap::new();
       for (key, value) in results {
-        hashmap.insert(key, value?);
+        hashmap.insert(key, value.map_err(Arc::new));
       }
 
       Ok(hashmap)
@@ -97,6 +135,166 @@ impl<C: HttpClient + Send + Sync + 'static + Clone> Loader<DataLoaderRequest> fo
   }
 }
 
+/// Upstreams usually cap the length of the URLs they accept,
+/// so merged requests with longer URLs are split into smaller batches.
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Merges the keys into as few requests as possible, splitting them whenever the merged URL gets too long.
+/// Returns each merged request, or the reason the keys couldn't be merged, along with the range of keys it covers.
+fn split_batches(
+  keys: &[DataLoaderRequest],
+  ids: &[Vec<String>],
+  group_by: &GroupBy,
+) -> Vec<(anyhow::Result<reqwest::Request>, Range<usize>)> {
+  let mut batches = Vec::new();
+  if keys.is_empty() {
+    return batches;
+  }
+  // A stack of the ranges left to merge, starting with all the keys
+  #[allow(clippy::single_range_in_vec_init)]
+  let mut pending = vec![0..keys.len()];
+  while let Some(range) = pending.pop() {
+    match merge(&keys[range.clone()], &ids[range.clone()], group_by) {
+      Ok(request) if range.len() > 1 && request.url().as_str().len() > MAX_URL_LENGTH => {
+        let mid = range.start + range.len() / 2;
+        pending.push(mid..range.end);
+        pending.push(range.start..mid);
+      }
+      request => batches.push((request, range)),
+    }
+  }
+  batches
+}
+
+/// Merges all the keys into the first request, as per the batching settings.
+fn merge(keys: &[DataLoaderRequest], ids: &[Vec<String>], group_by: &GroupBy) -> anyhow::Result<reqwest::Request> {
+  let mut request = keys[0].to_request();
+
+  match (group_by.batch_key(), group_by.encoding()) {
+    (Some(batch_key), _) => {
+      let bodies = keys.iter().map(parse_body).collect::<Vec<_>>();
+      let body = serde_json::json!({ batch_key: bodies });
+      request.body_mut().replace(serde_json::to_vec(&body)?.into());
+    }
+    (None, BatchEncoding::Repeat) => {
+      let first_url = request.url_mut();
+      for key in &keys[1..] {
+        let request = key.to_request();
+        first_url.query_pairs_mut().extend_pairs(request.url().query_pairs());
+      }
+    }
+    (None, BatchEncoding::Comma) => {
+      let query_params = group_by.query_params();
+      let url = request.url_mut();
+      let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| !query_params.contains(&k.as_ref()))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<Vec<_>>();
+      let mut query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish();
+      for (i, query_param) in query_params.iter().enumerate() {
+        // Each id is encoded on its own, so that the commas joining them are sent as they are
+        let joined = ids.iter().map(|id| encode(&id[i])).collect::<Vec<_>>().join(",");
+        if !query.is_empty() {
+          query.push('&');
+        }
+        query.push_str(&format!("{}={}", encode(query_param), joined));
+      }
+      url.set_query(Some(&query));
+    }
+    (None, BatchEncoding::Path) => {
+      let joined = join_ids(ids, 0);
+      request
+        .url_mut()
+        .path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Unable to batch ids into the URL path"))?
+        .pop()
+        .push(&joined);
+    }
+  }
+
+  Ok(request)
+}
+
+fn encode(text: &str) -> String {
+  form_urlencoded::byte_serialize(text.as_bytes()).collect()
+}
+
+fn join_ids(ids: &[Vec<String>], index: usize) -> String {
+  ids.iter().map(|id| id[index].as_str()).collect::<Vec<_>>().join(",")
+}
+
+/// Reads the id of each key from its `query_params`.
+fn query_param_ids(keys: &[DataLoaderRequest], query_params: &[&str]) -> Vec<anyhow::Result<Vec<String>>> {
+  keys
+    .iter()
+    .map(|data_loader_request| {
+      let req = data_loader_request.to_request();
+      let query_set: HashMap<_, _> = req.url().query_pairs().collect();
+      query_params
+        .iter()
+        .map(|query_param| {
+          query_set
+            .get(*query_param)
+            .map(|id| id.to_string())
+            .ok_or(anyhow::anyhow!("Unable to find key {} in query params", query_param))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+    })
+    .collect()
+}
+
+/// Reads the id of each key from the last segment of its URL path.
+fn path_ids(keys: &[DataLoaderRequest]) -> Vec<anyhow::Result<Vec<String>>> {
+  keys
+    .iter()
+    .map(|data_loader_request| {
+      let req = data_loader_request.to_request();
+      req
+        .url()
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| vec![segment.to_string()])
+        .ok_or(anyhow::anyhow!("Unable to find key in {} path", req.url()))
+    })
+    .collect()
+}
+
+fn parse_body(data_loader_request: &DataLoaderRequest) -> serde_json::Value {
+  let body = data_loader_request.body().unwrap_or_default();
+  serde_json::from_slice(body).unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(body).to_string()))
+}
+
+/// Reads the id of each key from its JSON body, which is either the body itself
+/// or, when the body is an object, the values of its `fields`.
+fn body_ids(keys: &[DataLoaderRequest], fields: &[&str]) -> Vec<anyhow::Result<Vec<String>>> {
+  keys
+    .iter()
+    .map(|data_loader_request| {
+      let body = parse_body(data_loader_request);
+      fields
+        .iter()
+        .map(|field| {
+          match &body {
+            serde_json::Value::Object(map) => map.get(*field),
+            value if fields.len() == 1 => Some(value),
+            _ => None,
+          }
+          .and_then(|id| match id {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+          })
+          .ok_or(anyhow::anyhow!("Unable to find key {} in request body", field))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+    })
+    .collect()
+}
+
 #[cfg(test)]
 mod tests {
   use std::collections::BTreeSet;
@@ -123,7 +321,7 @@ mod tests {
   async fn test_load_function() {
     let client = MockHttpClient { request_count: Arc::new(AtomicUsize::new(0)) };
 
-    let loader = HttpDataLoader { client: client.clone(), batched: None };
+    let loader = HttpDataLoader { client: Arc::new(client.clone()), batched: None, response_format: None };
     let loader = loader.to_data_loader(Batch::default().delay(1));
 
     let request = reqwest::Request::new(reqwest::Method::GET, "http://example.com".parse().unwrap());
@@ -141,7 +339,7 @@ mod tests {
   async fn test_load_function_many() {
     let client = MockHttpClient { request_count: Arc::new(AtomicUsize::new(0)) };
 
-    let loader = HttpDataLoader { client: client.clone(), batched: None };
+    let loader = HttpDataLoader { client: Arc::new(client.clone()), batched: None, response_format: None };
     let loader = loader.to_data_loader(Batch::default().delay(1));
 
     let request1 = reqwest::Request::new(reqwest::Method::GET, "http://example.com/1".parse().unwrap());
@@ -159,4 +357,386 @@ mod tests {
       "Only two requests should be made for two unique keys"
     );
   }
+
+  #[derive(Clone)]
+  struct MockBatchHttpClient {
+    request_bodies: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+  }
+
+  #[async_trait::async_trait]
+  impl HttpClient for MockBatchHttpClient {
+    async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+      let body: serde_json::Value = serde_json::from_slice(req.body().unwrap().as_bytes().unwrap())?;
+      self.request_bodies.lock().unwrap().push(body.clone());
+      let users = body["ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|id| serde_json::json!({"id": id, "name": format!("user-{}", id)}))
+        .collect::<Vec<_>>();
+      Ok(Response::default().body(ConstValue::from_json(serde_json::Value::Array(users))?))
+    }
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_batch_key() {
+    let client = MockBatchHttpClient { request_bodies: Arc::new(std::sync::Mutex::new(vec![])) };
+    let group_by = GroupBy::new(vec!["id".to_string()]).with_batch_key(Some("ids".to_string()));
+    let loader = HttpDataLoader::new(Arc::new(client.clone()), Some(group_by), None);
+    let loader = loader.to_data_loader(Batch::default().delay(1));
+
+    let keys = (1..=3).map(|id| {
+      let mut request = reqwest::Request::new(reqwest::Method::POST, "http://example.com/users".parse().unwrap());
+      request.body_mut().replace(id.to_string().into());
+      DataLoaderRequest::new(request, BTreeSet::new())
+    });
+    let results = join_all(keys.map(|key| loader.load_one(key))).await;
+
+    let names = results
+      .into_iter()
+      .map(|result| result.unwrap().unwrap().unwrap().body)
+      .map(|body| body.into_json().unwrap()["name"].clone())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["user-1", "user-2", "user-3"]);
+
+    let request_bodies = client.request_bodies.lock().unwrap();
+    assert_eq!(
+      request_bodies.len(),
+      1,
+      "All the keys should be merged into one request"
+    );
+    let mut ids = request_bodies[0]["ids"].as_array().unwrap().clone();
+    ids.sort_by_key(|id| id.as_i64());
+    assert_eq!(ids, vec![1, 2, 3]);
+  }
+
+  #[derive(Clone)]
+  struct MockOwnerHttpClient {
+    request_urls: Arc<std::sync::Mutex<Vec<String>>>,
+  }
+
+  #[async_trait::async_trait]
+  impl HttpClient for MockOwnerHttpClient {
+    async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+      self.request_urls.lock().unwrap().push(req.url().to_string());
+      let tenants = req.url().query_pairs().filter(|(k, _)| k == "tenant");
+      let owners = req.url().query_pairs().filter(|(k, _)| k == "ownerIds");
+      let items = tenants
+        .zip(owners)
+        .map(|((_, tenant), (_, owner))| serde_json::json!({"tenantId": tenant, "owner": {"id": owner.parse::<i64>().unwrap()}}))
+        .collect::<Vec<_>>();
+      Ok(Response::default().body(ConstValue::from_json(serde_json::json!({"data": {"items": items}}))?))
+    }
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_composite_key() {
+    use crate::config::group_by::GroupByKey;
+
+    let client = MockOwnerHttpClient { request_urls: Arc::new(std::sync::Mutex::new(vec![])) };
+    let path = |path: &[&str]| path.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    let group_by = GroupBy::from_keys(vec![
+      GroupByKey { path: path(&["data", "items", "tenantId"]), query_param: Some("tenant".to_string()) },
+      GroupByKey { path: path(&["data", "items", "owner", "id"]), query_param: Some("ownerIds".to_string()) },
+    ]);
+    let loader = HttpDataLoader::new(Arc::new(client.clone()), Some(group_by), None);
+    let loader = loader.to_data_loader(Batch::default().delay(1));
+
+    let keys = [("a", 1), ("b", 1), ("a", 2)].map(|(tenant, owner)| {
+      let url = format!("http://example.com/items?tenant={}&ownerIds={}", tenant, owner);
+      DataLoaderRequest::new(
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+        BTreeSet::new(),
+      )
+    });
+    let results = join_all(keys.map(|key| loader.load_one(key))).await;
+
+    let items = results
+      .into_iter()
+      .map(|result| result.unwrap().unwrap().unwrap().body.into_json().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      items,
+      vec![
+        serde_json::json!({"tenantId": "a", "owner": {"id": 1}}),
+        serde_json::json!({"tenantId": "b", "owner": {"id": 1}}),
+        serde_json::json!({"tenantId": "a", "owner": {"id": 2}}),
+      ]
+    );
+    assert_eq!(client.request_urls.lock().unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_nested_key() {
+    use crate::config::group_by::GroupByKey;
+
+    let client = MockOwnerHttpClient { request_urls: Arc::new(std::sync::Mutex::new(vec![])) };
+    let path = |path: &[&str]| path.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    let group_by = GroupBy::from_keys(vec![GroupByKey {
+      path: path(&["data", "items", "owner", "id"]),
+      query_param: Some("ownerIds".to_string()),
+    }])
+    .with_item_path(Some(path(&["data", "items"])));
+    let loader = HttpDataLoader::new(Arc::new(client.clone()), Some(group_by), None);
+    let loader = loader.to_data_loader(Batch::default().delay(1));
+
+    let keys = [1, 2].map(|owner| {
+      let url = format!("http://example.com/items?tenant=a&ownerIds={}", owner);
+      DataLoaderRequest::new(
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+        BTreeSet::new(),
+      )
+    });
+    let results = join_all(keys.map(|key| loader.load_one(key))).await;
+
+    let items = results
+      .into_iter()
+      .map(|result| result.unwrap().unwrap().unwrap().body.into_json().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      items,
+      vec![
+        serde_json::json!({"tenantId": "a", "owner": {"id": 1}}),
+        serde_json::json!({"tenantId": "a", "owner": {"id": 2}}),
+      ]
+    );
+  }
+
+  #[derive(Clone)]
+  struct MockUrlHttpClient {
+    request_urls: Arc<std::sync::Mutex<Vec<reqwest::Url>>>,
+  }
+
+  #[async_trait::async_trait]
+  impl HttpClient for MockUrlHttpClient {
+    async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+      self.request_urls.lock().unwrap().push(req.url().clone());
+      let ids = match req.url().query_pairs().find(|(k, _)| k == "ids") {
+        Some((_, ids)) => ids.to_string(),
+        None => req.url().path_segments().unwrap().next_back().unwrap().to_string(),
+      };
+      let users = ids
+        .split(',')
+        .map(|id| serde_json::json!({"id": id, "name": format!("user-{}", id)}))
+        .collect::<Vec<_>>();
+      Ok(Response::default().body(ConstValue::from_json(serde_json::Value::Array(users))?))
+    }
+  }
+
+  async fn load_names(group_by: GroupBy, urls: Vec<String>) -> (Vec<serde_json::Value>, Vec<reqwest::Url>) {
+    let client = MockUrlHttpClient { request_urls: Arc::new(std::sync::Mutex::new(vec![])) };
+    let loader = HttpDataLoader::new(Arc::new(client.clone()), Some(group_by), None);
+    let loader = loader.to_data_loader(Batch::default().delay(1).max_size(1000));
+
+    let keys = urls.into_iter().map(|url| {
+      DataLoaderRequest::new(
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+        BTreeSet::new(),
+      )
+    });
+    let results = join_all(keys.map(|key| loader.load_one(key))).await;
+    let names = results
+      .into_iter()
+      .map(|result| result.unwrap().unwrap().unwrap().body.into_json().unwrap()["name"].clone())
+      .collect();
+    let request_urls = client.request_urls.lock().unwrap().clone();
+    (names, request_urls)
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_comma_encoding() {
+    let group_by = GroupBy::from_keys(vec![crate::config::group_by::GroupByKey {
+      path: vec!["id".to_string()],
+      query_param: Some("ids".to_string()),
+    }])
+    .with_encoding(BatchEncoding::Comma);
+    let urls = (1..=3)
+      .map(|id| format!("http://example.com/users?ids={}&fields=name", id))
+      .collect();
+    let (names, request_urls) = load_names(group_by, urls).await;
+
+    assert_eq!(names, vec!["user-1", "user-2", "user-3"]);
+    assert_eq!(request_urls.len(), 1);
+    assert_eq!(
+      request_urls[0].as_str(),
+      "http://example.com/users?fields=name&ids=1,2,3"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_path_encoding() {
+    let group_by = GroupBy::new(vec!["id".to_string()]).with_encoding(BatchEncoding::Path);
+    let urls = (1..=3).map(|id| format!("http://example.com/users/{}", id)).collect();
+    let (names, request_urls) = load_names(group_by, urls).await;
+
+    assert_eq!(names, vec!["user-1", "user-2", "user-3"]);
+    assert_eq!(request_urls.len(), 1);
+    assert_eq!(request_urls[0].as_str(), "http://example.com/users/1,2,3");
+  }
+
+  #[tokio::test]
+  async fn test_load_function_splits_long_urls() {
+    let group_by = GroupBy::new(vec!["id".to_string()]).with_encoding(BatchEncoding::Path);
+    let urls = (0..500)
+      .map(|id| format!("http://example.com/users/{:08}", id))
+      .collect();
+    let (names, request_urls) = load_names(group_by, urls).await;
+
+    assert_eq!(names.len(), 500);
+    assert!(names.iter().all(|name| name.is_string()));
+    assert!(request_urls.len() > 1);
+    assert!(request_urls.iter().all(|url| url.as_str().len() <= MAX_URL_LENGTH));
+  }
+
+  type Handler = dyn Fn(&reqwest::Request) -> anyhow::Result<Response> + Send + Sync;
+
+  #[derive(Clone)]
+  struct MockFnHttpClient(Arc<Handler>);
+
+  #[async_trait::async_trait]
+  impl HttpClient for MockFnHttpClient {
+    async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
+      (self.0)(&req)
+    }
+  }
+
+  async fn load_all(
+    client: MockFnHttpClient,
+    group_by: Option<GroupBy>,
+    urls: &[&str],
+  ) -> Vec<Result<Response, Arc<anyhow::Error>>> {
+    let loader = HttpDataLoader::new(Arc::new(client), group_by, None).to_data_loader(Batch::default().delay(1));
+    let keys = urls.iter().map(|url| {
+      DataLoaderRequest::new(
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+        BTreeSet::new(),
+      )
+    });
+    join_all(keys.map(|key| loader.load_one(key)))
+      .await
+      .into_iter()
+      .map(|result| result.unwrap().unwrap())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_load_function_fails_per_key() {
+    let client = MockFnHttpClient(Arc::new(|req| {
+      if req.url().path() == "/fail" {
+        Err(anyhow::anyhow!("connection reset"))
+      } else {
+        Ok(Response::default())
+      }
+    }));
+    let results = load_all(client, None, &["http://example.com/ok", "http://example.com/fail"]).await;
+
+    assert!(results[0].is_ok());
+    assert_eq!(results[1].as_ref().unwrap_err().to_string(), "connection reset");
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_error_status() {
+    let client = MockFnHttpClient(Arc::new(|_| {
+      let body = ConstValue::from_json(serde_json::json!({"message": "boom"}))?;
+      Ok(
+        Response::default()
+          .status(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+          .body(body),
+      )
+    }));
+    let urls = ["http://example.com/users?id=1", "http://example.com/users?id=2"];
+    let batched = load_all(client.clone(), Some(GroupBy::new(vec!["id".to_string()])), &urls).await;
+    let unbatched = load_all(client, None, &urls).await;
+
+    for result in batched.into_iter().chain(unbatched) {
+      assert_eq!(
+        result.unwrap_err().to_string(),
+        r#"Upstream responded with 500 Internal Server Error: {message: "boom"}"#
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn test_load_function_fails_keys_without_id() {
+    let client = MockFnHttpClient(Arc::new(|req| {
+      assert_eq!(req.url().query(), Some("id=1"));
+      Ok(Response::default().body(ConstValue::from_json(serde_json::json!([{"id": 1}]))?))
+    }));
+    let group_by = GroupBy::new(vec!["id".to_string()]);
+    let results = load_all(
+      client,
+      Some(group_by),
+      &["http://example.com/users?id=1", "http://example.com/users"],
+    )
+    .await;
+
+    assert_eq!(results[0].as_ref().unwrap().body.to_string(), "{id: 1}");
+    assert_eq!(
+      results[1].as_ref().unwrap_err().to_string(),
+      "Unable to find key id in query params"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_missing_key() {
+    let client = MockFnHttpClient(Arc::new(|_| {
+      Ok(Response::default().body(ConstValue::from_json(serde_json::json!([{"id": 1}]))?))
+    }));
+    let urls = ["http://example.com/users?id=1", "http://example.com/users?id=2"];
+
+    let results = load_all(client.clone(), Some(GroupBy::new(vec!["id".to_string()])), &urls).await;
+    assert_eq!(results[0].as_ref().unwrap().body.to_string(), "{id: 1}");
+    assert_eq!(results[1].as_ref().unwrap().body, ConstValue::Null);
+
+    let group_by = GroupBy::new(vec!["id".to_string()]).with_on_missing_key(OnMissingKey::Error);
+    let results = load_all(client, Some(group_by), &urls).await;
+    assert!(results[0].is_ok());
+    assert_eq!(
+      results[1].as_ref().unwrap_err().to_string(),
+      "Unable to find key 2 in the upstream response"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_load_function_with_response_format() {
+    let client = MockFnHttpClient(Arc::new(|_| {
+      let mut headers = reqwest::header::HeaderMap::new();
+      headers.insert(reqwest::header::CONTENT_TYPE, "text/plain".parse()?);
+      let raw = hyper::body::Bytes::from("id,name\n1,John\n2,Jane\n");
+      Ok(Response::default().headers(headers).raw(Some(raw)))
+    }));
+    let group_by = GroupBy::new(vec!["id".to_string()]);
+    let loader = HttpDataLoader::new(Arc::new(client), Some(group_by), Some(ResponseFormat::Csv))
+      .to_data_loader(Batch::default().delay(1));
+    let keys = ["http://example.com/users?id=1", "http://example.com/users?id=2"].map(|url| {
+      DataLoaderRequest::new(
+        reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+        BTreeSet::new(),
+      )
+    });
+    let results = join_all(keys.map(|key| loader.load_one(key))).await;
+    let names: Vec<_> = results
+      .into_iter()
+      .map(|result| result.unwrap().unwrap().unwrap().body.to_string())
+      .collect();
+
+    assert_eq!(names, vec![r#"{id: 1,name: "John"}"#, r#"{id: 2,name: "Jane"}"#]);
+  }
+
+  #[test]
+  fn test_body_ids_keep_large_integers() {
+    let keys = [9007199254740992u64, 9007199254740993u64].map(|id| {
+      let mut request = reqwest::Request::new(reqwest::Method::POST, "http://example.com/users".parse().unwrap());
+      request
+        .body_mut()
+        .replace(serde_json::json!({"id": id}).to_string().into());
+      DataLoaderRequest::new(request, BTreeSet::new())
+    });
+    let ids = body_ids(&keys, &["id"])
+      .into_iter()
+      .map(|id| id.unwrap())
+      .collect::<Vec<_>>();
+
+    assert_eq!(ids, vec![vec!["9007199254740992"], vec!["9007199254740993"]]);
+  }
 }
@@ -15,10 +15,15 @@ impl DataLoaderRequest {
   pub fn headers(&self) -> &BTreeSet<String> {
     &self.1
   }
+  pub fn body(&self) -> Option<&[u8]> {
+    self.0.body().and_then(|body| body.as_bytes())
+  }
 }
 impl Hash for DataLoaderRequest {
   fn hash<H: Hasher>(&self, state: &mut H) {
+    self.0.method().hash(state);
     self.0.url().hash(state);
+    self.body().hash(state);
     for name in &self.1 {
       if let Some(value) = self.0.headers().get(name) {
         name.hash(state);
@@ -44,8 +49,11 @@ impl PartialEq for DataLoaderRequest {
 
 impl Clone for DataLoaderRequest {
   fn clone(&self) -> Self {
-    let mut req = reqwest::Request::new(reqwest::Method::GET, self.0.url().clone());
+    let mut req = reqwest::Request::new(self.0.method().clone(), self.0.url().clone());
     req.headers_mut().extend(self.0.headers().clone());
+    if let Some(body) = self.body() {
+      req.body_mut().replace(body.to_vec().into());
+    }
     DataLoaderRequest(req, self.1.clone())
   }
 }
@@ -117,7 +125,7 @@ mod tests {
     let key1 = create_endpoint_key("http://localhost:8080", vec![], BTreeSet::new());
     let req = reqwest::Request::new(reqwest::Method::POST, "http://localhost:8080".parse().unwrap());
     let key2 = DataLoaderRequest::new(req, BTreeSet::new());
-    assert_eq!(key1, key2);
+    assert_ne!(key1, key2);
   }
 
   #[test]
@@ -184,6 +192,25 @@ mod tests {
     assert_eq!(key1, key2);
   }
 
+  #[test]
+  fn test_different_bodies() {
+    let mut req1 = reqwest::Request::new(reqwest::Method::POST, "http://localhost:8080".parse().unwrap());
+    req1.body_mut().replace("1".into());
+    let mut req2 = reqwest::Request::new(reqwest::Method::POST, "http://localhost:8080".parse().unwrap());
+    req2.body_mut().replace("2".into());
+    let key1 = DataLoaderRequest::new(req1, BTreeSet::new());
+    let key2 = DataLoaderRequest::new(req2, BTreeSet::new());
+    assert_ne!(key1, key2);
+    assert_eq!(key1.clone().body(), Some("1".as_bytes()));
+  }
+
+  #[test]
+  fn test_clone_preserves_method() {
+    let req = reqwest::Request::new(reqwest::Method::POST, "http://localhost:8080".parse().unwrap());
+    let key = DataLoaderRequest::new(req, BTreeSet::new());
+    assert_eq!(key.clone().to_request().method(), reqwest::Method::POST);
+  }
+
   #[test]
   fn test_partial_eq_trait() {
     let key1 = create_endpoint_key(
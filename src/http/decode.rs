@@ -0,0 +1,290 @@
+use async_graphql::Value;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+
+use super::{xml, Response};
+use crate::config::ResponseFormat;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DecodeError {
+  #[error("invalid JSON: {0}")]
+  Json(String),
+
+  #[error("invalid XML: {0}")]
+  Xml(String),
+
+  #[error("invalid CSV: {0}")]
+  Csv(String),
+
+  #[error("invalid NDJSON: {0}")]
+  Ndjson(String),
+}
+
+/// The format of a response, read from its `Content-Type`.
+pub fn content_format(headers: &HeaderMap) -> Option<ResponseFormat> {
+  let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+  let mime = content_type.split(';').next()?.trim().to_lowercase();
+  match mime.as_str() {
+    "application/json" => Some(ResponseFormat::Json),
+    "application/xml" | "text/xml" => Some(ResponseFormat::Xml),
+    "text/csv" => Some(ResponseFormat::Csv),
+    "application/x-ndjson" | "application/ndjson" | "application/jsonl" | "application/x-jsonlines" => {
+      Some(ResponseFormat::Ndjson)
+    }
+    mime if mime.ends_with("+json") => Some(ResponseFormat::Json),
+    mime if mime.ends_with("+xml") => Some(ResponseFormat::Xml),
+    mime if mime.starts_with("text/") => Some(ResponseFormat::Text),
+    _ => None,
+  }
+}
+
+/// Decodes the text of a response:
+/// - XML elements become objects keyed by the name of their children, with attributes prefixed by `@` and
+///   the text of elements that also have attributes or children under `#text`
+/// - CSV rows become objects keyed by the header row
+/// - NDJSON lines become the items of a list
+pub fn decode(format: &ResponseFormat, text: &str) -> Result<Value, DecodeError> {
+  let json = match format {
+    ResponseFormat::Json => serde_json::from_str(text).map_err(|e| DecodeError::Json(e.to_string()))?,
+    ResponseFormat::Xml => xml::to_json(text).map_err(DecodeError::Xml)?,
+    ResponseFormat::Csv => csv_to_json(text).map_err(DecodeError::Csv)?,
+    ResponseFormat::Ndjson => ndjson_to_json(text)?,
+    ResponseFormat::Text => serde_json::Value::String(text.to_string()),
+  };
+  Value::from_json(json).map_err(|e| DecodeError::Json(e.to_string()))
+}
+
+impl Response {
+  /// Decodes the body as received, in the given format, or else the one of its `Content-Type`, or else as JSON.
+  /// Bodies that were already decoded, or that were built in place, are left as they are.
+  pub fn decode(mut self, format: Option<&ResponseFormat>) -> Result<Self, DecodeError> {
+    let Some(raw) = self.raw.take() else {
+      return Ok(self);
+    };
+    let text = String::from_utf8_lossy(&raw);
+    self.body = match (format, content_format(&self.headers)) {
+      (Some(format), _) => decode(format, &text)?,
+      (None, Some(ResponseFormat::Text)) => {
+        // JSON is often served as plain text
+        decode(&ResponseFormat::Json, &text).or_else(|_| decode(&ResponseFormat::Text, &text))?
+      }
+      (None, Some(format)) => decode(&format, &text)?,
+      (None, None) => decode(&ResponseFormat::Json, &text)?,
+    };
+    Ok(self)
+  }
+}
+
+/// Reads a scalar from text, keeping it a string unless it's written the way JSON writes numbers and booleans.
+pub fn infer_scalar(text: &str) -> serde_json::Value {
+  match text {
+    "true" => serde_json::Value::Bool(true),
+    "false" => serde_json::Value::Bool(false),
+    text => match serde_json::from_str::<serde_json::Number>(text) {
+      Ok(number) if number.to_string() == text => serde_json::Value::Number(number),
+      _ => serde_json::Value::String(text.to_string()),
+    },
+  }
+}
+
+fn csv_to_json(text: &str) -> Result<serde_json::Value, String> {
+  let mut reader = csv::Reader::from_reader(text.as_bytes());
+  let header = reader.headers().map_err(|e| e.to_string())?.clone();
+  reader
+    .records()
+    .map(|record| {
+      let record = record.map_err(|e| e.to_string())?;
+      let fields = header.iter().zip(record.iter()).map(|(name, value)| {
+        let value = if value.is_empty() {
+          serde_json::Value::Null
+        } else {
+          infer_scalar(value)
+        };
+        (name.to_string(), value)
+      });
+      Ok(serde_json::Value::Object(fields.collect()))
+    })
+    .collect::<Result<Vec<_>, _>>()
+    .map(serde_json::Value::Array)
+}
+
+fn ndjson_to_json(text: &str) -> Result<serde_json::Value, DecodeError> {
+  text
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty())
+    .map(|(i, line)| serde_json::from_str(line).map_err(|e| DecodeError::Ndjson(format!("line {}: {}", i + 1, e))))
+    .collect::<Result<Vec<_>, _>>()
+    .map(serde_json::Value::Array)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use async_graphql::Value;
+  use pretty_assertions::assert_eq;
+  use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+  use serde_json::json;
+
+  use super::{content_format, decode, DecodeError};
+  use crate::builder::TailcallBuilder;
+  use crate::config::{Config, ResponseFormat};
+  use crate::http::{MockHttpClient, RequestContext, Response};
+
+  fn headers(content_type: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers
+  }
+
+  #[test]
+  fn test_content_format() {
+    assert_eq!(
+      content_format(&headers("application/json; charset=utf-8")),
+      Some(ResponseFormat::Json)
+    );
+    assert_eq!(
+      content_format(&headers("application/problem+json")),
+      Some(ResponseFormat::Json)
+    );
+    assert_eq!(content_format(&headers("text/xml")), Some(ResponseFormat::Xml));
+    assert_eq!(
+      content_format(&headers("application/atom+xml")),
+      Some(ResponseFormat::Xml)
+    );
+    assert_eq!(content_format(&headers("text/csv")), Some(ResponseFormat::Csv));
+    assert_eq!(
+      content_format(&headers("application/x-ndjson")),
+      Some(ResponseFormat::Ndjson)
+    );
+    assert_eq!(content_format(&headers("text/plain")), Some(ResponseFormat::Text));
+    assert_eq!(content_format(&headers("application/octet-stream")), None);
+    assert_eq!(content_format(&HeaderMap::new()), None);
+  }
+
+  #[test]
+  fn test_csv() {
+    let text = "id,name,note\r\n1,\"Doe, John\",\"said \"\"hi\"\"\"\r\n2,Jane,\n";
+    let value = decode(&ResponseFormat::Csv, text).unwrap();
+    assert_eq!(
+      value.into_json().unwrap(),
+      json!([
+        {"id": 1, "name": "Doe, John", "note": "said \"hi\""},
+        {"id": 2, "name": "Jane", "note": null}
+      ])
+    );
+  }
+
+  #[test]
+  fn test_csv_errors() {
+    assert_eq!(
+      decode(&ResponseFormat::Csv, "id,name\n1\n").unwrap_err(),
+      DecodeError::Csv(
+        "CSV error: record 1 (line: 2, byte: 8): found record with 1 fields, but the previous record has 2 fields"
+          .to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn test_ndjson() {
+    let value = decode(&ResponseFormat::Ndjson, "{\"id\": 1}\n\n{\"id\": 2}\n").unwrap();
+    assert_eq!(value.into_json().unwrap(), json!([{"id": 1}, {"id": 2}]));
+
+    let error = decode(&ResponseFormat::Ndjson, "{\"id\": 1}\n{\"id\":").unwrap_err();
+    assert!(matches!(error, DecodeError::Ndjson(message) if message.starts_with("line 2:")));
+  }
+
+  #[test]
+  fn test_infer_scalar() {
+    assert_eq!(super::infer_scalar("12"), json!(12));
+    assert_eq!(super::infer_scalar("1.5"), json!(1.5));
+    assert_eq!(super::infer_scalar("true"), json!(true));
+    assert_eq!(super::infer_scalar("007"), json!("007"));
+    assert_eq!(super::infer_scalar("1e3"), json!("1e3"));
+  }
+
+  #[test]
+  fn test_response_decode() {
+    let raw = || Some("id\n1\n".into());
+    let response = Response::default().headers(headers("text/csv")).raw(raw());
+    let response = response.decode(None).unwrap();
+    assert_eq!(response.body.into_json().unwrap(), json!([{"id": 1}]));
+
+    // A CSV served as plain text, read as CSV explicitly
+    let response = Response::default().headers(headers("text/plain")).raw(raw());
+    let response = response.decode(Some(&ResponseFormat::Csv)).unwrap();
+    assert_eq!(response.body.into_json().unwrap(), json!([{"id": 1}]));
+
+    // A CSV served without a content type, read as CSV explicitly
+    let response = Response::default().raw(raw());
+    let response = response.decode(Some(&ResponseFormat::Csv)).unwrap();
+    assert_eq!(response.body.into_json().unwrap(), json!([{"id": 1}]));
+
+    // JSON served as plain text
+    let response = Response::default()
+      .headers(headers("text/plain"))
+      .raw(Some("{\"id\": 1}".into()));
+    let response = response.decode(None).unwrap();
+    assert_eq!(response.body.into_json().unwrap(), json!({"id": 1}));
+
+    // Bodies without a content type are read as JSON
+    let error = Response::default().raw(raw()).decode(None).unwrap_err();
+    assert!(matches!(error, DecodeError::Json(_)));
+
+    // Bodies that were built in place are left as they are
+    let response = Response::default().body(Value::String("id\n1\n".to_string()));
+    let response = response.decode(Some(&ResponseFormat::Csv)).unwrap();
+    assert_eq!(response.body, Value::String("id\n1\n".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_response_format() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+        }
+
+        type Query {
+          users: [User] @http(path: "/users.csv", responseFormat: CSV)
+          broken: [User] @http(path: "/users.xml")
+        }
+
+        type User {
+          id: Int
+          name: String
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    // Responds with CSV served as a binary file, or with malformed XML
+    let client = MockHttpClient(|req: &reqwest::Request| {
+      let (content_type, body) = match req.url().path() {
+        "/users.csv" => ("application/octet-stream", "id,name\n1,John\n2,Jane\n"),
+        _ => ("application/xml", "<users><user></users>"),
+      };
+      Ok(
+        Response::default()
+          .headers(headers(content_type))
+          .raw(Some(body.into())),
+      )
+    });
+    let server_ctx = TailcallBuilder::new(config).http_client(client).build().unwrap();
+    let req_ctx = Arc::new(RequestContext::from(&server_ctx));
+    let request = async_graphql::Request::new("{ users { id name } }").data(req_ctx.clone());
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.data.into_json().unwrap(),
+      json!({"users": [{"id": 1, "name": "John"}, {"id": 2, "name": "Jane"}]})
+    );
+
+    let request = async_graphql::Request::new("{ broken { id } }").data(req_ctx);
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.errors[0].message,
+      "DecodeError: invalid XML: expected '</user>', found '</users>' at position 20"
+    );
+  }
+}
@@ -0,0 +1,321 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use super::Response;
+
+/// What an `onRequest` script resolved the upstream call to.
+pub enum Intercepted {
+  /// The request to send upstream, possibly modified by the script.
+  Request(reqwest::Request),
+  /// A synthetic response, the request is never sent upstream.
+  Response(Response),
+}
+
+/// The value bound to `ctx` in an `onRequest` script,
+/// eg. `{method: "GET", url: "http://...", headers: {...}, body: null}`.
+pub fn request_to_value(request: &reqwest::Request) -> ConstValue {
+  let body = request
+    .body()
+    .and_then(|body| body.as_bytes())
+    .map(|body| ConstValue::String(String::from_utf8_lossy(body).to_string()))
+    .unwrap_or(ConstValue::Null);
+
+  object([
+    ("method", ConstValue::String(request.method().to_string())),
+    ("url", ConstValue::String(request.url().to_string())),
+    ("headers", headers_to_value(request.headers())),
+    ("body", body),
+  ])
+}
+
+/// Applies the result of an `onRequest` script to the request.
+/// A `null` result leaves the request unchanged, an object with a `response`
+/// key short-circuits the call, and any other object overrides the fields it sets.
+pub fn apply_request(mut request: reqwest::Request, value: ConstValue) -> Result<Intercepted> {
+  let mut fields = match value {
+    ConstValue::Null => return Ok(Intercepted::Request(request)),
+    ConstValue::Object(fields) => fields,
+    value => return Err(anyhow!("onRequest must return an object or null, found: {}", value)),
+  };
+
+  if let Some(response) = fields.swap_remove("response") {
+    return apply_response(Response::default(), response).map(Intercepted::Response);
+  }
+
+  if let Some(method) = fields.swap_remove("method") {
+    *request.method_mut() = reqwest::Method::from_str(&to_string(method)?.to_uppercase())?;
+  }
+  if let Some(url) = fields.swap_remove("url") {
+    *request.url_mut() = reqwest::Url::parse(&to_string(url)?)?;
+  }
+  if let Some(headers) = fields.swap_remove("headers") {
+    *request.headers_mut() = value_to_headers(headers)?;
+  }
+  if let Some(body) = fields.swap_remove("body") {
+    *request.body_mut() = match body {
+      ConstValue::Null => None,
+      ConstValue::String(body) => Some(body.into()),
+      body => Some(body.to_string().into()),
+    };
+  }
+
+  Ok(Intercepted::Request(request))
+}
+
+/// The value bound to `ctx.response` in an `onResponse` script,
+/// eg. `{status: 200, headers: {...}, body: {...}}`.
+/// Bodies are read in the format of their `Content-Type`, and passed as text when that fails.
+pub fn response_to_value(response: &Response) -> ConstValue {
+  let body = match response.clone().decode(None) {
+    Ok(response) => response.body,
+    Err(_) => ConstValue::String(String::from_utf8_lossy(response.raw.as_deref().unwrap_or_default()).into_owned()),
+  };
+  object([
+    ("status", ConstValue::from(response.status.as_u16())),
+    ("headers", headers_to_value(&response.headers)),
+    ("body", body),
+  ])
+}
+
+/// Applies the result of an `onResponse` script to the response.
+/// A `null` result leaves the response unchanged, an object overrides the fields it sets.
+pub fn apply_response(mut response: Response, value: ConstValue) -> Result<Response> {
+  let mut fields = match value {
+    ConstValue::Null => return Ok(response),
+    ConstValue::Object(fields) => fields,
+    value => return Err(anyhow!("a response must be an object or null, found: {}", value)),
+  };
+
+  if let Some(status) = fields.swap_remove("status") {
+    let status = match status {
+      ConstValue::Number(status) => status.as_u64().and_then(|status| u16::try_from(status).ok()),
+      _ => None,
+    }
+    .ok_or(anyhow!("status must be a valid HTTP status code"))?;
+    response.status = reqwest::StatusCode::from_u16(status)?;
+  }
+  if let Some(headers) = fields.swap_remove("headers") {
+    response.headers = value_to_headers(headers)?;
+  }
+  if let Some(body) = fields.swap_remove("body") {
+    response.body = body;
+    response.raw = None;
+  }
+
+  Ok(response)
+}
+
+fn object<const N: usize>(fields: [(&str, ConstValue); N]) -> ConstValue {
+  ConstValue::Object(
+    fields
+      .into_iter()
+      .map(|(name, value)| (Name::new(name), value))
+      .collect(),
+  )
+}
+
+fn headers_to_value(headers: &HeaderMap) -> ConstValue {
+  let mut fields = IndexMap::new();
+  for (name, value) in headers {
+    if let Ok(value) = value.to_str() {
+      fields.insert(Name::new(name.as_str()), ConstValue::String(value.to_string()));
+    }
+  }
+  ConstValue::Object(fields)
+}
+
+fn value_to_headers(value: ConstValue) -> Result<HeaderMap> {
+  let ConstValue::Object(fields) = value else {
+    return Err(anyhow!("headers must be an object, found: {}", value));
+  };
+  let mut headers = HeaderMap::new();
+  for (name, value) in fields {
+    headers.insert(
+      HeaderName::from_bytes(name.as_bytes())?,
+      HeaderValue::from_str(&to_string(value)?)?,
+    );
+  }
+  Ok(headers)
+}
+
+fn to_string(value: ConstValue) -> Result<String> {
+  match value {
+    ConstValue::String(value) => Ok(value),
+    ConstValue::Number(value) => Ok(value.to_string()),
+    ConstValue::Boolean(value) => Ok(value.to_string()),
+    value => Err(anyhow!("expected a string, found: {}", value)),
+  }
+}
+
+#[cfg(feature = "unsafe-js")]
+mod js {
+  use std::future::Future;
+  use std::sync::Arc;
+  use std::time::Duration;
+
+  use anyhow::Result;
+  use async_graphql_value::ConstValue;
+
+  use super::{apply_request, apply_response, object, request_to_value, response_to_value, Intercepted};
+  use crate::config::UpstreamScripts;
+  use crate::http::{HttpClient, Response};
+  use crate::javascript;
+
+  /// Runs the `@upstream(scripts:)` hooks around an upstream call.
+  #[derive(Clone)]
+  pub struct JsInterceptor {
+    runtime: Arc<javascript::Runtime>,
+    scripts: UpstreamScripts,
+    timeout: Duration,
+  }
+
+  impl JsInterceptor {
+    /// The runtime must have been created with `scripts` registered.
+    pub fn new(runtime: Arc<javascript::Runtime>, scripts: UpstreamScripts, timeout: Duration) -> Self {
+      Self { runtime, scripts, timeout }
+    }
+
+    pub async fn execute<F, Fut>(&self, request: reqwest::Request, send: F) -> Result<Response>
+    where
+      F: FnOnce(reqwest::Request) -> Fut,
+      Fut: Future<Output = Result<Response>>,
+    {
+      let request = match &self.scripts.on_request {
+        Some(script) => {
+          let ctx = request_to_value(&request);
+          match apply_request(request, self.run(script, ctx).await?)? {
+            Intercepted::Request(request) => request,
+            Intercepted::Response(response) => return Ok(response),
+          }
+        }
+        None => request,
+      };
+
+      match &self.scripts.on_response {
+        Some(script) => {
+          let request_value = request_to_value(&request);
+          let response = send(request).await?;
+          let ctx = object([("request", request_value), ("response", response_to_value(&response))]);
+          apply_response(response, self.run(script, ctx).await?)
+        }
+        None => send(request).await,
+      }
+    }
+
+    async fn run(&self, script: &str, ctx: ConstValue) -> Result<ConstValue> {
+      Ok(self.runtime.execute(script, ctx, Some(self.timeout)).await?)
+    }
+  }
+
+  /// Runs the `@upstream(scripts:)` hooks around every request sent by `client`.
+  pub struct InterceptedHttpClient {
+    client: Arc<dyn HttpClient>,
+    interceptor: JsInterceptor,
+  }
+
+  impl InterceptedHttpClient {
+    pub fn new(client: Arc<dyn HttpClient>, interceptor: JsInterceptor) -> Self {
+      Self { client, interceptor }
+    }
+  }
+
+  #[async_trait::async_trait]
+  impl HttpClient for InterceptedHttpClient {
+    async fn execute(&self, req: reqwest::Request) -> Result<Response> {
+      self.interceptor.execute(req, |req| self.client.execute(req)).await
+    }
+  }
+}
+
+#[cfg(feature = "unsafe-js")]
+pub use js::{InterceptedHttpClient, JsInterceptor};
+
+#[cfg(test)]
+mod tests {
+  use async_graphql_value::ConstValue;
+
+  use super::{apply_request, apply_response, request_to_value, response_to_value, Intercepted};
+  use crate::http::Response;
+
+  fn json(value: serde_json::Value) -> ConstValue {
+    ConstValue::from_json(value).unwrap()
+  }
+
+  fn request() -> reqwest::Request {
+    let mut request = reqwest::Request::new(reqwest::Method::POST, "http://localhost/users".parse().unwrap());
+    request.headers_mut().insert("x-id", "1".parse().unwrap());
+    *request.body_mut() = Some(r#"{"name":"foo"}"#.into());
+    request
+  }
+
+  #[test]
+  fn test_request_to_value() {
+    let expected = json(serde_json::json!({
+      "method": "POST",
+      "url": "http://localhost/users",
+      "headers": {"x-id": "1"},
+      "body": r#"{"name":"foo"}"#
+    }));
+    assert_eq!(request_to_value(&request()), expected);
+  }
+
+  #[test]
+  fn test_apply_request_overrides_fields() {
+    let value = json(serde_json::json!({"url": "http://localhost/users?sig=abc", "headers": {"x-sig": "abc"}}));
+    let Intercepted::Request(request) = apply_request(request(), value).unwrap() else {
+      panic!("expected a request")
+    };
+    assert_eq!(request.method(), reqwest::Method::POST);
+    assert_eq!(request.url().as_str(), "http://localhost/users?sig=abc");
+    assert_eq!(request.headers().get("x-sig").unwrap(), "abc");
+    assert!(request.headers().get("x-id").is_none());
+    assert_eq!(request.body().unwrap().as_bytes().unwrap(), br#"{"name":"foo"}"#);
+  }
+
+  #[test]
+  fn test_apply_request_null_is_unchanged() {
+    let Intercepted::Request(request) = apply_request(request(), ConstValue::Null).unwrap() else {
+      panic!("expected a request")
+    };
+    assert_eq!(request.url().as_str(), "http://localhost/users");
+    assert_eq!(request.headers().get("x-id").unwrap(), "1");
+  }
+
+  #[test]
+  fn test_apply_request_synthetic_response() {
+    let value = json(serde_json::json!({"response": {"status": 201, "body": {"id": 1}}}));
+    let Intercepted::Response(response) = apply_request(request(), value).unwrap() else {
+      panic!("expected a response")
+    };
+    assert_eq!(response.status, reqwest::StatusCode::CREATED);
+    assert_eq!(response.body, json(serde_json::json!({"id": 1})));
+  }
+
+  #[test]
+  fn test_apply_request_invalid() {
+    assert!(apply_request(request(), ConstValue::from(1)).is_err());
+    assert!(apply_request(request(), json(serde_json::json!({"method": "NOT A METHOD"}))).is_err());
+    assert!(apply_request(request(), json(serde_json::json!({"headers": ["x-id"]}))).is_err());
+  }
+
+  #[test]
+  fn test_apply_response() {
+    let response = Response::default().body(json(serde_json::json!({"data": {"id": 1}})));
+    let value = response_to_value(&response);
+    assert_eq!(
+      value,
+      json(serde_json::json!({"status": 200, "headers": {}, "body": {"data": {"id": 1}}}))
+    );
+
+    let value = json(serde_json::json!({"body": {"id": 1}}));
+    let response = apply_response(response, value).unwrap();
+    assert_eq!(response.status, reqwest::StatusCode::OK);
+    assert_eq!(response.body, json(serde_json::json!({"id": 1})));
+
+    assert!(apply_response(response, json(serde_json::json!({"status": 1000}))).is_err());
+  }
+}
@@ -2,12 +2,14 @@ mod client;
 mod data_loader;
 
 mod data_loader_request;
-mod memo_client;
+mod decode;
+mod interceptor;
 mod method;
 mod request_context;
 mod response;
 mod server;
 mod server_context;
+mod xml;
 
 use std::time::Duration;
 
@@ -15,11 +17,13 @@ use cache_control::CacheControl;
 pub use client::*;
 pub use data_loader::*;
 pub use data_loader_request::*;
+pub use decode::{content_format, DecodeError};
 use hyper::header::CACHE_CONTROL;
+pub use interceptor::*;
 pub use method::Method;
 pub use request_context::RequestContext;
 pub use response::*;
-pub use server::start_server;
+pub use server::{serve, start_server, TailcallService};
 pub use server_context::ServerContext;
 
 pub fn max_age(res: &Response) -> Option<Duration> {
@@ -1,19 +1,37 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
 
+use async_graphql::futures_util::future::{BoxFuture, FutureExt, Shared};
 use derive_setters::Setters;
 use hyper::HeaderMap;
 
-use super::{DefaultHttpClient, Response, ServerContext};
+use super::{DataLoaderRequest, DefaultHttpClient, HttpClient, Response, ServerContext};
 use crate::blueprint::Server;
 use crate::config::{self, Upstream};
+use crate::has_uploads::Upload;
+#[cfg(feature = "unsafe-js")]
+use crate::javascript;
+
+type MemoizedResponse = Shared<BoxFuture<'static, Result<Response, Arc<anyhow::Error>>>>;
 
 #[derive(Setters)]
 pub struct RequestContext {
-  pub http_client: DefaultHttpClient,
+  pub http_client: Arc<dyn HttpClient>,
   pub server: Server,
   pub upstream: Upstream,
   pub req_headers: HeaderMap,
+  pub req_method: Option<hyper::Method>,
+  pub req_path: Option<String>,
+  pub req_ip: Option<IpAddr>,
+  pub operation_name: Option<String>,
+  pub uploads: Vec<Upload>,
+  #[cfg(feature = "unsafe-js")]
+  #[setters(strip_option)]
+  pub js_runtime: Option<Arc<javascript::Runtime>>,
   min_max_age: Arc<Mutex<Option<u64>>>,
+  memo: Arc<Mutex<HashMap<DataLoaderRequest, MemoizedResponse>>>,
 }
 
 impl Default for RequestContext {
@@ -21,18 +39,82 @@ impl Default for RequestContext {
     let config = config::Config::default();
     //TODO: default is used only in tests. Drop default and move it to test.
     let server = Server::try_from(config.server.clone()).unwrap();
-    RequestContext::new(DefaultHttpClient::default(), server, config.upstream.clone())
+    RequestContext::new(Arc::new(DefaultHttpClient::default()), server, config.upstream.clone())
   }
 }
 
 impl RequestContext {
-  pub fn new(http_client: DefaultHttpClient, server: Server, upstream: Upstream) -> Self {
-    Self { req_headers: HeaderMap::new(), http_client, server, upstream, min_max_age: Arc::new(Mutex::new(None)) }
+  pub fn new(http_client: Arc<dyn HttpClient>, server: Server, upstream: Upstream) -> Self {
+    Self {
+      req_headers: HeaderMap::new(),
+      req_method: None,
+      req_path: None,
+      req_ip: None,
+      operation_name: None,
+      uploads: Vec::new(),
+      http_client,
+      server,
+      upstream,
+      #[cfg(feature = "unsafe-js")]
+      js_runtime: None,
+      min_max_age: Arc::new(Mutex::new(None)),
+      memo: Arc::new(Mutex::new(HashMap::new())),
+    }
   }
 
+  /// Executes the request upstream.
+  /// GET requests are memoized for the lifetime of the context, so identical
+  /// calls made while resolving a single operation hit the upstream only once.
   pub async fn execute(&self, req: reqwest::Request) -> anyhow::Result<Response> {
-    Ok(self.http_client.execute(req).await?)
+    if req.method() != reqwest::Method::GET {
+      return self.http_client.execute(req).await;
+    }
+
+    let headers = req.headers().keys().map(|name| name.to_string()).collect();
+    let http_client = self.http_client.clone();
+    self
+      .memoize(DataLoaderRequest::new(req, headers), |key| async move {
+        http_client.execute(key.to_request()).await
+      })
+      .await
+  }
+
+  /// Returns the response memoized for `key`, or runs `load` and memoizes its result.
+  /// Concurrent callers with the same key share a single in-flight `load`, and failed
+  /// loads are evicted so that later calls retry them.
+  pub async fn memoize<F, Fut>(&self, key: DataLoaderRequest, load: F) -> anyhow::Result<Response>
+  where
+    F: FnOnce(DataLoaderRequest) -> Fut,
+    Fut: Future<Output = anyhow::Result<Response>> + Send + 'static,
+  {
+    let response = {
+      let mut memo = self.memo.lock().unwrap();
+      match memo.get(&key) {
+        Some(response) => response.clone(),
+        None => {
+          let response = load(key.clone())
+            .map(|result| result.map_err(Arc::new))
+            .boxed()
+            .shared();
+          memo.insert(key.clone(), response.clone());
+          response
+        }
+      }
+    };
+
+    match response.clone().await {
+      Ok(response) => Ok(response),
+      Err(e) => {
+        let mut memo = self.memo.lock().unwrap();
+        // Another caller may have already evicted it and started a retry
+        if memo.get(&key).is_some_and(|memoized| memoized.ptr_eq(&response)) {
+          memo.remove(&key);
+        }
+        Err(anyhow::anyhow!("{:#}", e))
+      }
+    }
   }
+
   fn set_min_max_age_conc(&self, min_max_age: u64) {
     *self.min_max_age.lock().unwrap() = Some(min_max_age);
   }
@@ -57,18 +139,94 @@ impl RequestContext {
 impl From<&ServerContext> for RequestContext {
   fn from(server_ctx: &ServerContext) -> Self {
     let http_client = server_ctx.http_client.clone();
-    Self::new(
-      http_client,
-      server_ctx.blueprint.server.clone(),
-      server_ctx.blueprint.upstream.clone(),
-    )
+    Self {
+      #[cfg(feature = "unsafe-js")]
+      js_runtime: Some(server_ctx.js_runtime.clone()),
+      ..Self::new(
+        http_client,
+        server_ctx.blueprint.server.clone(),
+        server_ctx.blueprint.upstream.clone(),
+      )
+    }
   }
 }
 
 #[cfg(test)]
 mod test {
+  use std::collections::BTreeSet;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::Arc;
+
+  use async_graphql::futures_util::future::join_all;
 
-  use crate::http::RequestContext;
+  use crate::http::{DataLoaderRequest, RequestContext, Response};
+
+  fn key(url: &str) -> DataLoaderRequest {
+    DataLoaderRequest::new(
+      reqwest::Request::new(reqwest::Method::GET, url.parse().unwrap()),
+      BTreeSet::new(),
+    )
+  }
+
+  async fn load_all(req_ctx: &RequestContext, urls: &[&str], count: Arc<AtomicUsize>) {
+    let futures = urls.iter().map(|url| {
+      let count = count.clone();
+      req_ctx.memoize(key(url), |_| async move {
+        count.fetch_add(1, Ordering::SeqCst);
+        Ok(Response::default())
+      })
+    });
+    for result in join_all(futures).await {
+      result.unwrap();
+    }
+  }
+
+  #[tokio::test]
+  async fn test_memoize_identical_requests() {
+    let req_ctx = RequestContext::default();
+    let count = Arc::new(AtomicUsize::new(0));
+    load_all(&req_ctx, &["http://example.com/1"; 10], count.clone()).await;
+    load_all(&req_ctx, &["http://example.com/1"], count.clone()).await;
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+  }
+
+  #[tokio::test]
+  async fn test_memoize_distinct_requests() {
+    let req_ctx = RequestContext::default();
+    let count = Arc::new(AtomicUsize::new(0));
+    load_all(
+      &req_ctx,
+      &["http://example.com/1", "http://example.com/2"],
+      count.clone(),
+    )
+    .await;
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+  }
+
+  #[tokio::test]
+  async fn test_memoize_evicts_failures() {
+    let req_ctx = RequestContext::default();
+    let count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..2 {
+      let count = count.clone();
+      let result = req_ctx
+        .memoize(key("http://example.com/1"), |_| async move {
+          count.fetch_add(1, Ordering::SeqCst);
+          Err(anyhow::anyhow!("connection refused").context("upstream failed"))
+        })
+        .await;
+      assert_eq!(result.unwrap_err().to_string(), "upstream failed: connection refused");
+    }
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+  }
+
+  #[tokio::test]
+  async fn test_memoize_is_request_scoped() {
+    let count = Arc::new(AtomicUsize::new(0));
+    load_all(&RequestContext::default(), &["http://example.com/1"], count.clone()).await;
+    load_all(&RequestContext::default(), &["http://example.com/1"], count.clone()).await;
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+  }
 
   #[test]
   fn test_update_max_age_less_than_existing() {
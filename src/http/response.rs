@@ -1,12 +1,15 @@
 use anyhow::Result;
 use derive_setters::Setters;
 use http_cache_semantics::ResponseLike;
+use hyper::body::Bytes;
 
 #[derive(Clone, Debug, Default, Setters)]
 pub struct Response {
   pub status: reqwest::StatusCode,
   pub headers: reqwest::header::HeaderMap,
   pub body: async_graphql::Value,
+  /// The body as received from the upstream, until `decode` reads it into `body`.
+  pub raw: Option<Bytes>,
 }
 
 impl Response {
@@ -14,9 +17,39 @@ impl Response {
     let status = resp.status();
     let headers = resp.headers().to_owned();
     let body = resp.bytes().await?;
-    let json = serde_json::from_slice(&body)?;
-    Ok(Response { status, headers, body: json })
+    if status.is_success() {
+      // Decoded by `decode`, once the format expected by the endpoint is known
+      return Ok(Response { status, headers, body: Default::default(), raw: Some(body) });
+    }
+    match serde_json::from_slice(&body) {
+      Ok(json) => Ok(Response { status, headers, body: json, raw: None }),
+      Err(_) => Err(status_error(status, &String::from_utf8_lossy(&body))),
+    }
   }
+
+  /// Fails with the status and an excerpt of the body unless the upstream responded with a 2xx.
+  pub fn error_for_status(self) -> Result<Self> {
+    if self.status.is_success() {
+      Ok(self)
+    } else {
+      let body = match &self.raw {
+        Some(raw) => String::from_utf8_lossy(raw).into_owned(),
+        None => self.body.to_string(),
+      };
+      Err(status_error(self.status, &body))
+    }
+  }
+}
+
+/// Upper bound on the number of characters of the upstream body quoted in errors.
+const MAX_EXCERPT_LENGTH: usize = 256;
+
+fn status_error(status: reqwest::StatusCode, body: &str) -> anyhow::Error {
+  let excerpt = match body.char_indices().nth(MAX_EXCERPT_LENGTH) {
+    Some((end, _)) => format!("{}...", &body[..end]),
+    None => body.to_string(),
+  };
+  anyhow::anyhow!("Upstream responded with {}: {}", status, excerpt)
 }
 
 impl ResponseLike for Response {
@@ -1,17 +1,24 @@
 use std::collections::BTreeSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use anyhow::Result;
-use async_graphql::http::GraphiQLSource;
-use hyper::service::{make_service_fn, service_fn};
+use async_graphql::futures_util::future::BoxFuture;
+use async_graphql::http::{GraphiQLSource, MultipartOptions};
+use async_graphql::parser::types::DocumentOperations;
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, Service};
 use hyper::{Body, HeaderMap, Request, Response, StatusCode};
 
 use super::request_context::RequestContext;
 use super::ServerContext;
 use crate::async_graphql_hyper;
-use crate::blueprint::Blueprint;
+use crate::builder::TailcallBuilder;
 use crate::cli::CLIError;
 use crate::config::Config;
+use crate::has_uploads::Upload;
 
 fn graphiql() -> Result<Response<Body>> {
   Ok(Response::new(Body::from(
@@ -23,9 +30,30 @@ async fn graphql_request(req: Request<Body>, server_ctx: &ServerContext) -> Resu
   let upstream = server_ctx.blueprint.upstream.clone();
   let allowed = upstream.get_allowed_headers();
   let headers = create_allowed_headers(req.headers(), &allowed);
+  let method = req.method().clone();
+  let path = req.uri().path().to_string();
+  let ip = req.extensions().get::<SocketAddr>().map(SocketAddr::ip);
+  let content_type = req
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .map(String::from);
   let bytes = hyper::body::to_bytes(req.into_body()).await?;
-  let request: async_graphql_hyper::GraphQLRequest = serde_json::from_slice(&bytes)?;
-  let req_ctx = Arc::new(RequestContext::from(server_ctx).req_headers(headers));
+  let (request, uploads) = match content_type {
+    Some(content_type) if content_type.starts_with("multipart/form-data") => {
+      multipart_request(content_type, bytes).await?
+    }
+    _ => (serde_json::from_slice(&bytes)?, vec![]),
+  };
+  let req_ctx = Arc::new(
+    RequestContext::from(server_ctx)
+      .req_headers(headers)
+      .uploads(uploads)
+      .req_method(Some(method))
+      .req_path(Some(path))
+      .req_ip(ip)
+      .operation_name(operation_name(&request.0)),
+  );
   let mut response = request.data(req_ctx.clone()).execute(&server_ctx.schema).await;
 
   if server_ctx.blueprint.server.enable_cache_control_header {
@@ -42,6 +70,37 @@ async fn graphql_request(req: Request<Body>, server_ctx: &ServerContext) -> Resu
 
   Ok(resp)
 }
+/// Reads a request following the GraphQL multipart request spec, along with the files it uploads.
+async fn multipart_request(
+  content_type: String,
+  bytes: hyper::body::Bytes,
+) -> Result<(async_graphql_hyper::GraphQLRequest, Vec<Upload>)> {
+  let mut request = async_graphql::http::receive_body(
+    Some(content_type),
+    async_graphql::futures_util::io::Cursor::new(bytes),
+    MultipartOptions::default(),
+  )
+  .await?;
+  let uploads = std::mem::take(&mut request.uploads)
+    .into_iter()
+    .map(Upload::try_from)
+    .collect::<std::io::Result<Vec<_>>>()?;
+  Ok((async_graphql_hyper::GraphQLRequest(request), uploads))
+}
+
+/// The operation the client asked for, or the only named operation of the document.
+fn operation_name(request: &async_graphql::Request) -> Option<String> {
+  if request.operation_name.is_some() {
+    return request.operation_name.clone();
+  }
+  match async_graphql::parser::parse_query(&request.query).ok()?.operations {
+    DocumentOperations::Multiple(operations) if operations.len() == 1 => {
+      operations.into_keys().next().map(|name| name.to_string())
+    }
+    _ => None,
+  }
+}
+
 fn not_found() -> Result<Response<Body>> {
   Ok(Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty())?)
 }
@@ -62,12 +121,64 @@ fn create_allowed_headers(headers: &HeaderMap, allowed: &BTreeSet<String>) -> He
 
   new_headers
 }
+/// Handles GraphQL requests on `/graphql`, and serves the playground when it's enabled.
+/// It's a `tower::Service`, so it can be mounted in an existing hyper or tower app.
+/// The caller's address, available to templates as `{{request.ip}}`, is read from
+/// a `SocketAddr` request extension, or set per connection with [`Self::remote_addr`].
+#[derive(Clone)]
+pub struct TailcallService {
+  state: Arc<ServerContext>,
+  remote_addr: Option<SocketAddr>,
+}
+
+impl TailcallService {
+  pub fn new(server_ctx: ServerContext) -> Self {
+    Self { state: Arc::new(server_ctx), remote_addr: None }
+  }
+
+  pub fn remote_addr(mut self, remote_addr: SocketAddr) -> Self {
+    self.remote_addr = Some(remote_addr);
+    self
+  }
+
+  pub fn server_context(&self) -> &ServerContext {
+    &self.state
+  }
+
+  /// Handles a request in-process, without going through the network.
+  pub async fn execute(&self, req: Request<Body>) -> Result<Response<Body>> {
+    handle_request(req, self.state.clone()).await
+  }
+}
+
+impl Service<Request<Body>> for TailcallService {
+  type Response = Response<Body>;
+  type Error = anyhow::Error;
+  type Future = BoxFuture<'static, Result<Response<Body>>>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+    if let Some(remote_addr) = self.remote_addr {
+      req.extensions_mut().insert(remote_addr);
+    }
+    Box::pin(handle_request(req, self.state.clone()))
+  }
+}
+
 pub async fn start_server(config: Config) -> Result<()> {
-  let blueprint = Blueprint::try_from(&config).map_err(CLIError::from)?;
-  let state = Arc::new(ServerContext::new(blueprint.clone()));
-  let make_svc = make_service_fn(move |_conn| {
-    let state = Arc::clone(&state);
-    async move { Ok::<_, anyhow::Error>(service_fn(move |req| handle_request(req, state.clone()))) }
+  TailcallBuilder::new(config).start().await
+}
+
+/// Serves the server on the hostname and port of its blueprint.
+pub async fn serve(server_ctx: ServerContext) -> Result<()> {
+  let blueprint = server_ctx.blueprint.clone();
+  let service = TailcallService::new(server_ctx);
+  let make_svc = make_service_fn(move |conn: &AddrStream| {
+    let service = service.clone().remote_addr(conn.remote_addr());
+    async move { Ok::<_, anyhow::Error>(service) }
   });
   let addr = (blueprint.server.hostname, blueprint.server.port).into();
   let server = hyper::Server::try_bind(&addr).map_err(CLIError::from)?.serve(make_svc);
@@ -4,28 +4,30 @@ use async_graphql::dynamic;
 use derive_setters::Setters;
 
 use crate::blueprint::{Blueprint, Definition};
-use crate::http::{DefaultHttpClient, HttpDataLoader};
+use crate::config::Batch;
+use crate::http::{DefaultHttpClient, HttpClient, HttpDataLoader};
+#[cfg(feature = "unsafe-js")]
+use crate::http::{InterceptedHttpClient, JsInterceptor};
+#[cfg(feature = "unsafe-js")]
+use crate::javascript;
 use crate::lambda::{Expression, Operation};
 
 #[derive(Setters, Clone)]
 pub struct ServerContext {
   pub schema: dynamic::Schema,
-  pub http_client: DefaultHttpClient,
+  pub http_client: Arc<dyn HttpClient>,
   pub blueprint: Blueprint,
+  #[cfg(feature = "unsafe-js")]
+  pub js_runtime: Arc<javascript::Runtime>,
 }
 
-fn assign_data_loaders(blueprint: &mut Blueprint, http_client: DefaultHttpClient) -> &Blueprint {
+fn assign_data_loaders(blueprint: &mut Blueprint, http_client: Arc<dyn HttpClient>) -> &Blueprint {
+  let batch = blueprint.upstream.batch.clone().unwrap_or_default();
   for def in blueprint.definitions.iter_mut() {
     if let Definition::ObjectTypeDefinition(def) = def {
       for field in &mut def.fields {
-        if let Some(Expression::Unsafe(Operation::Endpoint(req_template, group_by, _))) = &mut field.resolver {
-          let data_loader = HttpDataLoader::new(http_client.clone(), group_by.clone())
-            .to_data_loader(blueprint.upstream.batch.clone().unwrap_or_default());
-          field.resolver = Some(Expression::Unsafe(Operation::Endpoint(
-            req_template.clone(),
-            group_by.clone(),
-            Some(Arc::new(data_loader)),
-          )));
+        if let Some(expression) = &mut field.resolver {
+          assign_data_loader(expression, &http_client, &batch);
         }
       }
     }
@@ -33,10 +35,89 @@ fn assign_data_loaders(blueprint: &mut Blueprint, http_client: DefaultHttpClient
   blueprint
 }
 
+fn assign_data_loader(expression: &mut Expression, http_client: &Arc<dyn HttpClient>, batch: &Batch) {
+  match expression {
+    Expression::Unsafe(Operation::Endpoint(req_template, group_by, dl)) => {
+      let response_format = req_template.endpoint.response_format.clone();
+      let data_loader =
+        HttpDataLoader::new(http_client.clone(), group_by.clone(), response_format).to_data_loader(batch.clone());
+      *dl = Some(Arc::new(data_loader));
+    }
+    Expression::Pipe(steps) => {
+      for step in steps {
+        assign_data_loader(step, http_client, batch);
+      }
+    }
+    Expression::Transform(expr, _) => assign_data_loader(expr, http_client, batch),
+    _ => {}
+  }
+}
+
+#[cfg(feature = "unsafe-js")]
+fn collect_scripts(blueprint: &Blueprint) -> Vec<String> {
+  let mut scripts = blueprint
+    .upstream
+    .scripts
+    .as_ref()
+    .map(|scripts| scripts.scripts())
+    .unwrap_or_default();
+  for def in blueprint.definitions.iter() {
+    if let Definition::ObjectTypeDefinition(def) = def {
+      for field in &def.fields {
+        if let Some(expression) = &field.resolver {
+          collect_script(expression, &mut scripts);
+        }
+      }
+    }
+  }
+  scripts
+}
+
+#[cfg(feature = "unsafe-js")]
+fn collect_script(expression: &Expression, scripts: &mut Vec<String>) {
+  match expression {
+    Expression::Unsafe(Operation::JS(input, script)) => {
+      collect_script(input, scripts);
+      scripts.push(script.clone());
+    }
+    Expression::Pipe(steps) => {
+      for step in steps {
+        collect_script(step, scripts);
+      }
+    }
+    Expression::Transform(expr, _) => collect_script(expr, scripts),
+    _ => {}
+  }
+}
+
 impl ServerContext {
   pub fn new(blueprint: Blueprint) -> Self {
-    let http_client = DefaultHttpClient::new(blueprint.upstream.clone());
+    let http_client = Arc::new(DefaultHttpClient::new(blueprint.upstream.clone()));
+    Self::with_http_client(blueprint, http_client)
+  }
+
+  /// Sends the upstream requests with `http_client` instead of a client built from the `@upstream` settings.
+  pub fn with_http_client(blueprint: Blueprint, http_client: Arc<dyn HttpClient>) -> Self {
+    #[cfg(feature = "unsafe-js")]
+    let js_runtime = Arc::new(javascript::Runtime::new(
+      collect_scripts(&blueprint),
+      blueprint.server.script_workers,
+    ));
+    #[cfg(feature = "unsafe-js")]
+    let http_client = match blueprint.upstream.scripts.clone() {
+      Some(scripts) => Arc::new(InterceptedHttpClient::new(
+        http_client,
+        JsInterceptor::new(js_runtime.clone(), scripts, blueprint.server.script_timeout),
+      )),
+      None => http_client,
+    };
     let schema = assign_data_loaders(&mut blueprint.clone(), http_client.clone()).to_schema();
-    ServerContext { schema, http_client, blueprint }
+    ServerContext {
+      schema,
+      http_client,
+      #[cfg(feature = "unsafe-js")]
+      js_runtime,
+      blueprint,
+    }
   }
 }
@@ -0,0 +1,260 @@
+use super::decode::infer_scalar;
+
+/// Reads an XML document into JSON, eg. `<user id="1"><name>John</name></user>` into
+/// `{"user": {"@id": 1, "name": "John"}}`. Repeated children become lists, and the text of elements that also
+/// have attributes or children is kept under `#text`.
+pub fn to_json(text: &str) -> Result<serde_json::Value, String> {
+  let mut parser = Parser { input: text, pos: 0, depth: 0 };
+  parser.skip_misc()?;
+  let (name, value) = parser.element()?;
+  parser.skip_misc()?;
+  if parser.pos < parser.input.len() {
+    return Err(parser.error("unexpected content after the root element"));
+  }
+  Ok(serde_json::Value::Object([(name, value)].into_iter().collect()))
+}
+
+/// Upper bound on the nesting of elements, so that hostile documents can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+  input: &'a str,
+  pos: usize,
+  depth: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn rest(&self) -> &'a str {
+    &self.input[self.pos..]
+  }
+
+  fn error(&self, message: &str) -> String {
+    format!("{} at position {}", message, self.pos)
+  }
+
+  fn skip_whitespace(&mut self) {
+    let rest = self.rest();
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+
+  /// Skips up to and including `end`.
+  fn skip_past(&mut self, end: &str) -> Result<&'a str, String> {
+    match self.rest().find(end) {
+      Some(i) => {
+        let skipped = &self.rest()[..i];
+        self.pos += i + end.len();
+        Ok(skipped)
+      }
+      None => Err(self.error(&format!("expected '{end}'"))),
+    }
+  }
+
+  fn expect(&mut self, token: &str) -> Result<(), String> {
+    if self.rest().starts_with(token) {
+      self.pos += token.len();
+      Ok(())
+    } else {
+      Err(self.error(&format!("expected '{token}'")))
+    }
+  }
+
+  /// Skips the declaration, processing instructions, comments and doctype around elements.
+  fn skip_misc(&mut self) -> Result<(), String> {
+    loop {
+      self.skip_whitespace();
+      if self.rest().starts_with("<?") {
+        self.skip_past("?>")?;
+      } else if self.rest().starts_with("<!--") {
+        self.skip_past("-->")?;
+      } else if self.rest().starts_with("<!DOCTYPE") {
+        self.skip_past(">")?;
+      } else {
+        return Ok(());
+      }
+    }
+  }
+
+  fn name(&mut self) -> Result<String, String> {
+    let rest = self.rest();
+    let end = rest
+      .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+      .unwrap_or(rest.len());
+    if end == 0 {
+      return Err(self.error("expected a name"));
+    }
+    self.pos += end;
+    Ok(rest[..end].to_string())
+  }
+
+  fn element(&mut self) -> Result<(String, serde_json::Value), String> {
+    self.expect("<")?;
+    let name = self.name()?;
+    let mut fields = serde_json::Map::new();
+
+    loop {
+      self.skip_whitespace();
+      if self.rest().starts_with("/>") {
+        self.pos += 2;
+        return Ok((name, to_value(fields, String::new())));
+      }
+      if self.rest().starts_with('>') {
+        self.pos += 1;
+        break;
+      }
+      let attribute = self.name()?;
+      self.skip_whitespace();
+      self.expect("=")?;
+      self.skip_whitespace();
+      let quote = match self.rest().chars().next() {
+        Some(quote @ ('"' | '\'')) => quote,
+        _ => return Err(self.error("expected a quoted attribute value")),
+      };
+      self.pos += 1;
+      let value = unescape(self.skip_past(&quote.to_string())?)?;
+      fields.insert(format!("@{attribute}"), infer_scalar(&value));
+    }
+
+    let mut text = String::new();
+    loop {
+      if self.rest().starts_with("</") {
+        self.pos += 2;
+        let end = self.name()?;
+        if end != name {
+          return Err(self.error(&format!("expected '</{name}>', found '</{end}>'")));
+        }
+        self.skip_whitespace();
+        self.expect(">")?;
+        return Ok((name, to_value(fields, text)));
+      } else if self.rest().starts_with("<![CDATA[") {
+        self.pos += "<![CDATA[".len();
+        text.push_str(self.skip_past("]]>")?);
+      } else if self.rest().starts_with("<!--") {
+        self.skip_past("-->")?;
+      } else if self.rest().starts_with("<?") {
+        self.skip_past("?>")?;
+      } else if self.rest().starts_with('<') {
+        if self.depth == MAX_DEPTH {
+          return Err(self.error(&format!("elements are nested deeper than {MAX_DEPTH} levels")));
+        }
+        self.depth += 1;
+        let (child, value) = self.element()?;
+        self.depth -= 1;
+        match fields.get_mut(&child) {
+          Some(serde_json::Value::Array(items)) => items.push(value),
+          Some(existing) => *existing = serde_json::Value::Array(vec![existing.take(), value]),
+          None => {
+            fields.insert(child, value);
+          }
+        }
+      } else if self.rest().is_empty() {
+        return Err(self.error(&format!("unclosed element '{name}'")));
+      } else {
+        let end = self.rest().find('<').unwrap_or(self.rest().len());
+        text.push_str(&unescape(&self.rest()[..end])?);
+        self.pos += end;
+      }
+    }
+  }
+}
+
+fn to_value(mut fields: serde_json::Map<String, serde_json::Value>, text: String) -> serde_json::Value {
+  let text = text.trim();
+  match (fields.is_empty(), text.is_empty()) {
+    (true, true) => serde_json::Value::Null,
+    (true, false) => infer_scalar(text),
+    (false, true) => serde_json::Value::Object(fields),
+    (false, false) => {
+      fields.insert("#text".to_string(), infer_scalar(text));
+      serde_json::Value::Object(fields)
+    }
+  }
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+  let mut result = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    result.push_str(&rest[..start]);
+    let end = rest[start..]
+      .find(';')
+      .ok_or_else(|| format!("unterminated entity in '{text}'"))?;
+    let entity = &rest[start + 1..start + end];
+    let c = match entity {
+      "lt" => Some('<'),
+      "gt" => Some('>'),
+      "amp" => Some('&'),
+      "quot" => Some('"'),
+      "apos" => Some('\''),
+      entity => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        None => entity
+          .strip_prefix('#')
+          .and_then(|decimal| decimal.parse().ok())
+          .and_then(char::from_u32),
+      },
+    };
+    result.push(c.ok_or_else(|| format!("unknown entity '&{entity};'"))?);
+    rest = &rest[start + end + 1..];
+  }
+  result.push_str(rest);
+  Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::to_json;
+
+  #[test]
+  fn test_elements_and_attributes() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+      <!-- users -->
+      <users count="2">
+        <user id="1" active="true"><name>John &amp; Jane</name><tag>a</tag></user>
+        <user id="2"><name><![CDATA[<Bob>]]></name><tag>a</tag><tag>b</tag></user>
+        <note lang="en">Hello</note>
+        <empty/>
+      </users>"#;
+    assert_eq!(
+      to_json(xml).unwrap(),
+      json!({
+        "users": {
+          "@count": 2,
+          "user": [
+            {"@id": 1, "@active": true, "name": "John & Jane", "tag": "a"},
+            {"@id": 2, "name": "<Bob>", "tag": ["a", "b"]}
+          ],
+          "note": {"@lang": "en", "#text": "Hello"},
+          "empty": null
+        }
+      })
+    );
+  }
+
+  #[test]
+  fn test_entities() {
+    assert_eq!(to_json("<a>&lt;&#65;&#x42;&gt;</a>").unwrap(), json!({"a": "<AB>"}));
+  }
+
+  #[test]
+  fn test_errors() {
+    assert_eq!(
+      to_json("<a><b></a>").unwrap_err(),
+      "expected '</b>', found '</a>' at position 9"
+    );
+    assert_eq!(to_json("<a>").unwrap_err(), "unclosed element 'a' at position 3");
+    assert_eq!(
+      to_json("<a/><b/>").unwrap_err(),
+      "unexpected content after the root element at position 4"
+    );
+    assert_eq!(to_json("<a>&nbsp;</a>").unwrap_err(), "unknown entity '&nbsp;'");
+
+    let nested = format!("{}{}", "<a>".repeat(200), "</a>".repeat(200));
+    assert_eq!(
+      to_json(&nested).unwrap_err(),
+      "elements are nested deeper than 128 levels at position 387"
+    );
+  }
+}
@@ -1,53 +1,248 @@
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 use std::time::Duration;
 
-use mini_v8::{Error, MiniV8, Script};
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+use mini_v8::{Function, MiniV8, Script, Value};
+use tokio::sync::oneshot;
 
-// TODO: Performance optimizations
-// This function can be optimized quite heavily
-pub fn execute_js(
-  script: &str,
-  input: async_graphql::Value,
+const INPUT: &str = "__tailcall_input";
+const SCRIPT: &str = "__tailcall_script_";
+
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum Error {
+  #[error("evaluation timed out")]
+  Timeout,
+
+  #[error("{0}")]
+  Exception(String),
+}
+
+impl From<mini_v8::Error> for Error {
+  fn from(error: mini_v8::Error) -> Self {
+    match error {
+      mini_v8::Error::Timeout => Error::Timeout,
+      error => Error::Exception(error.to_string()),
+    }
+  }
+}
+
+struct Job {
+  script: usize,
+  input: ConstValue,
   timeout: Option<Duration>,
-) -> Result<async_graphql::Value, Error> {
+  reply: oneshot::Sender<Result<ConstValue, Error>>,
+}
+
+/// A pool of V8 isolates, each owned by a dedicated thread.
+/// Scripts are compiled once per isolate when the pool starts, so evaluating
+/// a script only passes its input across and calls the compiled function.
+pub struct Runtime {
+  scripts: HashMap<String, usize>,
+  sender: Option<Mutex<mpsc::Sender<Job>>>,
+}
+
+impl Runtime {
+  pub fn new(scripts: Vec<String>, workers: usize) -> Self {
+    let mut unique = Vec::new();
+    let mut indices = HashMap::new();
+    for script in scripts {
+      if !indices.contains_key(&script) {
+        indices.insert(script.clone(), unique.len());
+        unique.push(script);
+      }
+    }
+
+    if unique.is_empty() || workers == 0 {
+      return Self { scripts: indices, sender: None };
+    }
+
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let unique = Arc::new(unique);
+    for _ in 0..workers {
+      let receiver = receiver.clone();
+      let scripts = unique.clone();
+      thread::spawn(move || run_worker(&scripts, &receiver));
+    }
+
+    Self { scripts: indices, sender: Some(Mutex::new(sender)) }
+  }
+
+  /// Evaluates a script that was registered when the pool was created,
+  /// binding `input` to `ctx`.
+  pub async fn execute(&self, script: &str, input: ConstValue, timeout: Option<Duration>) -> Result<ConstValue, Error> {
+    let script = *self
+      .scripts
+      .get(script)
+      .ok_or_else(|| Error::Exception(format!("script is not registered: {}", script)))?;
+    let sender = self
+      .sender
+      .as_ref()
+      .ok_or_else(|| Error::Exception("JS runtime has no workers".to_string()))?;
+    let (reply, receiver) = oneshot::channel();
+    sender
+      .lock()
+      .unwrap()
+      .send(Job { script, input, timeout, reply })
+      .map_err(|_| Error::Exception("JS runtime has shut down".to_string()))?;
+
+    receiver
+      .await
+      .map_err(|_| Error::Exception("JS worker stopped before replying".to_string()))?
+  }
+}
+
+fn run_worker(scripts: &[String], receiver: &Mutex<mpsc::Receiver<Job>>) {
   let mv8 = MiniV8::new();
-  let source = create_source(script, input);
-  let value: String = mv8.eval(Script { source, timeout, origin: None })?;
-  let json = serde_json::from_str(value.as_str()).unwrap();
-  Ok(json)
+  let compiled = scripts
+    .iter()
+    .enumerate()
+    .map(|(index, script)| compile(&mv8, index, script))
+    .collect::<Vec<_>>();
+
+  loop {
+    // The lock is released as soon as a job is received, so idle workers can
+    // pick up the next job while this one is evaluating.
+    let job = receiver.lock().unwrap().recv();
+    let Ok(job) = job else { break };
+    let result = compiled[job.script]
+      .clone()
+      .and_then(|_| call(&mv8, job.script, job.input, job.timeout));
+    let _ = job.reply.send(result);
+  }
+}
+
+fn compile(mv8: &MiniV8, index: usize, script: &str) -> Result<(), Error> {
+  let function: Function = mv8.eval(format!("(function (ctx) {{ return ({}); }})", script))?;
+  mv8.global().set(format!("{}{}", SCRIPT, index), function)?;
+  Ok(())
+}
+
+fn call(mv8: &MiniV8, index: usize, input: ConstValue, timeout: Option<Duration>) -> Result<ConstValue, Error> {
+  mv8.global().set(INPUT, to_js(mv8, input)?)?;
+  let source = format!("{}{}({})", SCRIPT, index, INPUT);
+  let output: Value = mv8.eval(Script { source, timeout, origin: None })?;
+  from_js(output)
+}
+
+fn to_js(mv8: &MiniV8, value: ConstValue) -> Result<Value, Error> {
+  Ok(match value {
+    ConstValue::Null => Value::Null,
+    ConstValue::Boolean(value) => Value::Boolean(value),
+    ConstValue::Number(value) => Value::Number(value.as_f64().unwrap_or(f64::NAN)),
+    ConstValue::String(value) => Value::String(mv8.create_string(&value)),
+    ConstValue::Enum(value) => Value::String(mv8.create_string(value.as_str())),
+    ConstValue::Binary(value) => Value::String(mv8.create_string(&String::from_utf8_lossy(&value))),
+    ConstValue::List(values) => {
+      let array = mv8.create_array();
+      for value in values {
+        array.push(to_js(mv8, value)?)?;
+      }
+      Value::Array(array)
+    }
+    ConstValue::Object(fields) => {
+      let object = mv8.create_object();
+      for (name, value) in fields {
+        object.set(name.as_str(), to_js(mv8, value)?)?;
+      }
+      Value::Object(object)
+    }
+  })
 }
 
-fn create_source(script: &str, input: async_graphql::Value) -> String {
-  let template = "(function (ctx) {return JSON.stringify(--SCRIPT--)} )(--INPUT--);";
+fn from_js(value: Value) -> Result<ConstValue, Error> {
+  Ok(match value {
+    Value::Undefined | Value::Null | Value::Function(_) => ConstValue::Null,
+    Value::Boolean(value) => ConstValue::Boolean(value),
+    Value::Number(value) | Value::Date(value) => from_number(value),
+    Value::String(value) => ConstValue::String(value.to_string()),
+    Value::Array(array) => ConstValue::List(
+      array
+        .elements::<Value>()
+        .map(|value| from_js(value?))
+        .collect::<Result<_, _>>()?,
+    ),
+    Value::Object(object) => {
+      let mut fields = IndexMap::new();
+      for property in object.properties::<String, Value>(false)? {
+        let (name, value) = property?;
+        fields.insert(Name::new(name), from_js(value)?);
+      }
+      ConstValue::Object(fields)
+    }
+  })
+}
 
-  template
-    .replace("--SCRIPT--", script)
-    .replace("--INPUT--", input.to_string().as_str())
+/// Whole numbers are returned as integers, the way `JSON.parse` would read them.
+fn from_number(value: f64) -> ConstValue {
+  if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+    ConstValue::from(value as i64)
+  } else {
+    serde_json::Number::from_f64(value)
+      .map(ConstValue::Number)
+      .unwrap_or(ConstValue::Null)
+  }
 }
 
 #[cfg(test)]
-#[test]
-fn test_json() {
-  let json = r#"
+mod tests {
+  use std::time::Duration;
+
+  use super::{Error, Runtime};
+
+  fn runtime(script: &str) -> Runtime {
+    Runtime::new(vec![script.to_string()], 2)
+  }
+
+  #[tokio::test]
+  async fn test_json() {
+    let json = r#"
     {
         "name": "John Doe",
         "age": 43
     }
     "#;
-  let json = serde_json::from_str(json).unwrap();
-  let script = "ctx.name";
-  let actual = execute_js(script, json, Some(Duration::from_secs(1))).unwrap();
-  let expected = async_graphql::Value::from("John Doe");
+    let json = serde_json::from_str(json).unwrap();
+    let script = "ctx.name";
+    let actual = runtime(script)
+      .execute(script, json, Some(Duration::from_secs(1)))
+      .await
+      .unwrap();
+    let expected = async_graphql::Value::from("John Doe");
 
-  assert_eq!(actual, expected);
-}
+    assert_eq!(actual, expected);
+  }
 
-#[cfg(test)]
-#[test]
-fn test_timeout() {
-  let script = "(function () {while(true) {};})()";
-  let actual = execute_js(script, async_graphql::Value::Null, Some(Duration::from_millis(10)));
-  match actual {
-    Err(Error::Timeout) => {}                                      // Success case
-    _ => panic!("Expected a Timeout error, but got {:?}", actual), // Failure case
+  #[tokio::test]
+  async fn test_object_output() {
+    let json = serde_json::from_str(r#"{"ids": [1, 2]}"#).unwrap();
+    let script = "({count: ctx.ids.length, first: ctx.ids[0], half: ctx.ids[0] / 2})";
+    let actual = runtime(script)
+      .execute(script, json, Some(Duration::from_secs(1)))
+      .await
+      .unwrap();
+    let expected: async_graphql::Value = serde_json::from_str(r#"{"count": 2, "first": 1, "half": 0.5}"#).unwrap();
+
+    assert_eq!(actual, expected);
+  }
+
+  #[tokio::test]
+  async fn test_timeout() {
+    let script = "(function () {while(true) {};})()";
+    let actual = runtime(script)
+      .execute(script, async_graphql::Value::Null, Some(Duration::from_millis(10)))
+      .await;
+    assert_eq!(actual, Err(Error::Timeout));
+  }
+
+  #[tokio::test]
+  async fn test_unregistered_script() {
+    let actual = runtime("ctx")
+      .execute("ctx + 1", async_graphql::Value::Null, None)
+      .await;
+    assert!(actual.is_err());
   }
 }
@@ -10,6 +10,7 @@ pub trait JsonLike {
   fn as_i64_ok(&self) -> Result<i64, &str>;
   fn as_u64_ok(&self) -> Result<u64, &str>;
   fn as_f64_ok(&self) -> Result<f64, &str>;
+  fn as_number_ok(&self) -> Result<&serde_json::Number, &str>;
   fn as_bool_ok(&self) -> Result<bool, &str>;
   fn as_null_ok(&self) -> Result<(), &str>;
   fn as_option_ok(&self) -> Result<Option<&Self::Output>, &str>;
@@ -36,6 +37,12 @@ impl JsonLike for serde_json::Value {
   fn as_f64_ok(&self) -> Result<f64, &str> {
     self.as_f64().ok_or("expected f64")
   }
+  fn as_number_ok(&self) -> Result<&serde_json::Number, &str> {
+    match self {
+      serde_json::Value::Number(n) => Ok(n),
+      _ => Err("expected number"),
+    }
+  }
   fn as_bool_ok(&self) -> Result<bool, &str> {
     self.as_bool().ok_or("expected bool")
   }
@@ -127,6 +134,13 @@ impl JsonLike for async_graphql::Value {
     }
   }
 
+  fn as_number_ok(&self) -> Result<&serde_json::Number, &str> {
+    match self {
+      ConstValue::Number(n) => Ok(n),
+      _ => Err("number"),
+    }
+  }
+
   fn as_bool_ok(&self) -> Result<bool, &str> {
     match self {
       ConstValue::Boolean(b) => Ok(*b),
@@ -230,6 +244,52 @@ pub fn group_by_key<'a, J: JsonLike>(src: Vec<(&'a J, &'a J)>) -> HashMap<String
   map
 }
 
+/// Collects the items found at `path`, flattening any lists along the way.
+fn gather_items<'a, J: JsonLike>(root: &'a J, path: &[String], items: &mut Vec<&'a J>) {
+  if let Ok(root) = root.as_array_ok() {
+    for value in root {
+      gather_items(J::new(value), path, items);
+    }
+  } else if let Some((key, tail)) = path.split_first() {
+    if let Some(value) = root.get_key(key) {
+      gather_items(J::new(value), tail, items);
+    }
+  } else {
+    items.push(root);
+  }
+}
+
+/// Groups the items found at `path` by the values of `keys`, each of which is a path relative to the item.
+/// Items missing any of the keys are skipped. Numbers are keyed by their exact text, so large integers stay distinct.
+pub fn group_by_keys<'a, J: JsonLike<Output = J>>(
+  root: &'a J,
+  path: &[String],
+  keys: &[Vec<String>],
+) -> HashMap<Vec<String>, Vec<&'a J>> {
+  let mut items = Vec::new();
+  gather_items(root, path, &mut items);
+
+  let mut map: HashMap<Vec<String>, Vec<&'a J>> = HashMap::new();
+  for item in items {
+    let key = keys
+      .iter()
+      .map(|key| {
+        let value = item.get_path(key)?;
+        value
+          .as_string_ok()
+          .cloned()
+          .or_else(|_| value.as_number_ok().map(|a| a.to_string()))
+          .ok()
+      })
+      .collect::<Option<Vec<_>>>();
+
+    if let Some(key) = key {
+      map.entry(key).or_default().push(item);
+    }
+  }
+  map
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -237,7 +297,7 @@ mod tests {
   use serde_json::json;
 
   use crate::json::group_by_key;
-  use crate::json::json_like::gather_path_matches;
+  use crate::json::json_like::{gather_path_matches, group_by_keys};
 
   #[test]
   fn test_gather_path_matches() {
@@ -341,4 +401,56 @@ mod tests {
 
     assert_eq!(actual, expected)
   }
+
+  #[test]
+  fn test_group_by_keys_nested() {
+    let input = json!({
+        "data": [
+            {"user": {"id": "1"}},
+            {"user": [{"id": "2"}, {"id": 3}]},
+            {"user": {"name": "no-id"}},
+        ]
+    });
+
+    let actual = group_by_keys(&input, &["data".into(), "user".into()], &[vec!["id".into()]]);
+
+    assert_eq!(actual.len(), 3);
+    assert_eq!(actual[&vec!["1".to_string()]], vec![&json!({"id": "1"})]);
+    assert_eq!(actual[&vec!["2".to_string()]], vec![&json!({"id": "2"})]);
+    assert_eq!(actual[&vec!["3".to_string()]], vec![&json!({"id": 3})]);
+  }
+
+  #[test]
+  fn test_group_by_keys_composite() {
+    let input = json!([
+        {"tenantId": "a", "owner": {"id": 1}},
+        {"tenantId": "b", "owner": {"id": 1}},
+        {"tenantId": "a", "owner": {"id": 2}},
+    ]);
+
+    let actual = group_by_keys(
+      &input,
+      &[],
+      &[vec!["tenantId".into()], vec!["owner".into(), "id".into()]],
+    );
+
+    assert_eq!(actual.len(), 3);
+    assert_eq!(
+      actual[&vec!["b".to_string(), "1".to_string()]],
+      vec![&json!({"tenantId": "b", "owner": {"id": 1}})]
+    );
+  }
+
+  #[test]
+  fn test_group_by_keys_large_integers() {
+    let input = json!([{"id": 9007199254740992u64}, {"id": 9007199254740993u64}]);
+
+    let actual = group_by_keys(&input, &[], &[vec!["id".into()]]);
+
+    assert_eq!(actual.len(), 2);
+    assert_eq!(
+      actual[&vec!["9007199254740993".to_string()]],
+      vec![&json!({"id": 9007199254740993u64})]
+    );
+  }
 }
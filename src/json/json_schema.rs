@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use async_graphql::Name;
 use serde::{Deserialize, Serialize};
 
+use crate::scalar::Scalar;
 use crate::valid::Valid;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -14,6 +15,10 @@ pub enum JsonSchema {
   Str,
   Num,
   Bool,
+  /// Accepts any value, for when the shape isn't known upfront.
+  Any,
+  /// Accepts the values of a built-in scalar, eg. dates or email addresses.
+  Format(Scalar),
 }
 
 impl<const L: usize> From<[(&'static str, JsonSchema); L]> for JsonSchema {
@@ -44,6 +49,14 @@ impl JsonSchema {
         async_graphql::Value::Number(_) => Valid::succeed(()),
         _ => Valid::fail("expected number"),
       },
+      JsonSchema::Any => Valid::succeed(()),
+      JsonSchema::Format(scalar) => {
+        if scalar.validate(value) {
+          Valid::succeed(())
+        } else {
+          Valid::fail(scalar.expected())
+        }
+      }
       JsonSchema::Bool => match value {
         async_graphql::Value::Boolean(_) => Valid::succeed(()),
         _ => Valid::fail("expected boolean"),
@@ -105,6 +118,7 @@ mod tests {
   use indexmap::IndexMap;
 
   use crate::json::JsonSchema;
+  use crate::scalar::Scalar;
   use crate::valid::Valid;
 
   #[test]
@@ -115,6 +129,15 @@ mod tests {
     assert_eq!(result, Valid::succeed(()));
   }
 
+  #[test]
+  fn test_validate_format() {
+    let schema = JsonSchema::Format(Scalar::Email);
+    let value = async_graphql::Value::String("john@example.com".to_string());
+    assert_eq!(schema.validate(&value), Valid::succeed(()));
+    let value = async_graphql::Value::String("john".to_string());
+    assert_eq!(schema.validate(&value), Valid::fail("expected an email address"));
+  }
+
   #[test]
   fn test_validate_valid_object() {
     let schema = JsonSchema::from([("name", JsonSchema::Str), ("age", JsonSchema::Num)]);
@@ -0,0 +1,137 @@
+use crate::mustache::{Filter, Mustache};
+use crate::path_string::PathString;
+
+/// A JSON value whose strings are mustache templates, eg. `{"id": "{{args.id}}", "tags": ["new", "{{args.tag}}"]}`.
+/// Rendering keeps the JSON types: a string that is a single expression is replaced by the value it points to,
+/// so numbers stay numbers and `{{args.input}}` inserts the whole object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonTemplate {
+  Mustache(Mustache),
+  Array(Vec<JsonTemplate>),
+  Object(Vec<(String, JsonTemplate)>),
+  Const(serde_json::Value),
+}
+
+impl JsonTemplate {
+  pub fn parse(value: &serde_json::Value) -> anyhow::Result<JsonTemplate> {
+    Ok(match value {
+      serde_json::Value::String(s) => JsonTemplate::Mustache(Mustache::parse(s)?),
+      serde_json::Value::Array(items) => {
+        JsonTemplate::Array(items.iter().map(JsonTemplate::parse).collect::<anyhow::Result<_>>()?)
+      }
+      serde_json::Value::Object(map) => JsonTemplate::Object(
+        map
+          .iter()
+          .map(|(k, v)| Ok((k.clone(), JsonTemplate::parse(v)?)))
+          .collect::<anyhow::Result<_>>()?,
+      ),
+      value => JsonTemplate::Const(value.clone()),
+    })
+  }
+
+  pub fn render(&self, ctx: &impl PathString) -> serde_json::Value {
+    match self {
+      JsonTemplate::Mustache(mustache) => mustache.render_json(ctx),
+      JsonTemplate::Array(items) => serde_json::Value::Array(items.iter().map(|item| item.render(ctx)).collect()),
+      JsonTemplate::Object(fields) => {
+        serde_json::Value::Object(fields.iter().map(|(k, v)| (k.clone(), v.render(ctx))).collect())
+      }
+      JsonTemplate::Const(value) => value.clone(),
+    }
+  }
+
+  pub fn is_const(&self) -> bool {
+    match self {
+      JsonTemplate::Mustache(mustache) => mustache.is_const(),
+      JsonTemplate::Array(items) => items.iter().all(JsonTemplate::is_const),
+      JsonTemplate::Object(fields) => fields.iter().all(|(_, v)| v.is_const()),
+      JsonTemplate::Const(_) => true,
+    }
+  }
+
+  /// The path of each expression in the template, along with the filters it's piped through.
+  pub fn expressions(&self) -> Vec<(&Vec<String>, &[Filter])> {
+    match self {
+      JsonTemplate::Mustache(mustache) => mustache.expressions(),
+      JsonTemplate::Array(items) => items.iter().flat_map(JsonTemplate::expressions).collect(),
+      JsonTemplate::Object(fields) => fields.iter().flat_map(|(_, v)| v.expressions()).collect(),
+      JsonTemplate::Const(_) => vec![],
+    }
+  }
+
+  /// Rebuilds the template with each mustache replaced by `f`.
+  pub fn try_map<E>(&self, f: &impl Fn(&Mustache) -> Result<Mustache, E>) -> Result<JsonTemplate, E> {
+    Ok(match self {
+      JsonTemplate::Mustache(mustache) => JsonTemplate::Mustache(f(mustache)?),
+      JsonTemplate::Array(items) => {
+        JsonTemplate::Array(items.iter().map(|item| item.try_map(f)).collect::<Result<_, _>>()?)
+      }
+      JsonTemplate::Object(fields) => JsonTemplate::Object(
+        fields
+          .iter()
+          .map(|(k, v)| Ok((k.clone(), v.try_map(f)?)))
+          .collect::<Result<_, _>>()?,
+      ),
+      JsonTemplate::Const(value) => JsonTemplate::Const(value.clone()),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::JsonTemplate;
+
+  #[test]
+  fn test_render_typed_values() {
+    let template = JsonTemplate::parse(&json!({
+      "id": "{{args.id}}",
+      "input": "{{args.input}}",
+      "title": "Post {{args.id}}",
+      "tags": ["new", "{{args.tag | upper}}"],
+      "draft": false,
+      "missing": "{{args.missing}}"
+    }))
+    .unwrap();
+    let ctx = json!({"args": {"id": 1, "input": {"name": "foo", "ids": [1, 2]}, "tag": "rust"}});
+
+    assert_eq!(
+      template.render(&ctx),
+      json!({
+        "id": 1,
+        "input": {"name": "foo", "ids": [1, 2]},
+        "title": "Post 1",
+        "tags": ["new", "RUST"],
+        "draft": false,
+        "missing": null
+      })
+    );
+  }
+
+  #[test]
+  fn test_is_const() {
+    assert!(JsonTemplate::parse(&json!({"a": [1, "b"]})).unwrap().is_const());
+    assert!(!JsonTemplate::parse(&json!({"a": [1, "{{args.b}}"]}))
+      .unwrap()
+      .is_const());
+  }
+
+  #[test]
+  fn test_expressions() {
+    let template = JsonTemplate::parse(&json!({"a": "{{args.a}}", "b": ["{{value.b}}"]})).unwrap();
+    let paths = template
+      .expressions()
+      .into_iter()
+      .map(|(parts, _)| parts.join("."))
+      .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["args.a", "value.b"]);
+  }
+
+  #[test]
+  fn test_invalid_template() {
+    let error = JsonTemplate::parse(&json!({"a": ["{{args.a"]})).unwrap_err();
+    assert_eq!(error.to_string(), "invalid template expression '{{args.a'");
+  }
+}
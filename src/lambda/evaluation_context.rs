@@ -5,17 +5,16 @@ use derive_setters::Setters;
 use once_cell::sync::Lazy;
 use reqwest::header::HeaderMap;
 
-use super::{EmptyResolverContext, ResolverContextLike};
+use super::{DynResolverContext, EmptyResolverContext, ResolverContextLike};
 use crate::http::RequestContext;
 
 // TODO: rename to ResolverContext
 #[derive(Clone, Setters)]
 #[setters(strip_option)]
-pub struct EvaluationContext<'a, Ctx: ResolverContextLike<'a>> {
+pub struct EvaluationContext<'a, Ctx: ResolverContextLike<'a> + ?Sized> {
   pub req_ctx: &'a RequestContext,
   pub graphql_ctx: &'a Ctx,
 
-  // TODO: JS timeout should be read from server settings
   pub timeout: Duration,
 }
 
@@ -27,11 +26,18 @@ impl Default for EvaluationContext<'static, EmptyResolverContext> {
   }
 }
 
-impl<'a, Ctx: ResolverContextLike<'a>> EvaluationContext<'a, Ctx> {
+impl<'a, Ctx: ResolverContextLike<'a> + Send + Sync> EvaluationContext<'a, Ctx> {
   pub fn new(req_ctx: &'a RequestContext, graphql_ctx: &'a Ctx) -> EvaluationContext<'a, Ctx> {
-    Self { timeout: Duration::from_millis(5), req_ctx, graphql_ctx }
+    Self { timeout: req_ctx.server.script_timeout, req_ctx, graphql_ctx }
   }
 
+  /// Hides the type of the GraphQL context, so the context can be handed to a [`super::Resolver`].
+  pub fn erased(&self) -> EvaluationContext<'a, DynResolverContext<'a>> {
+    EvaluationContext { req_ctx: self.req_ctx, graphql_ctx: self.graphql_ctx, timeout: self.timeout }
+  }
+}
+
+impl<'a, Ctx: ResolverContextLike<'a> + ?Sized> EvaluationContext<'a, Ctx> {
   pub fn value(&self) -> Option<&Value> {
     self.graphql_ctx.value()
   }
@@ -46,6 +52,10 @@ impl<'a, Ctx: ResolverContextLike<'a>> EvaluationContext<'a, Ctx> {
     get_path_value(self.graphql_ctx.value()?, path)
   }
 
+  pub fn parent_value<T: AsRef<str>>(&self, path: &[T]) -> Option<&'a Value> {
+    get_path_value(self.graphql_ctx.parent()?, path)
+  }
+
   pub fn headers(&self) -> &HeaderMap {
     &self.req_ctx.req_headers
   }
@@ -61,6 +71,29 @@ impl<'a, Ctx: ResolverContextLike<'a>> EvaluationContext<'a, Ctx> {
 
     vars.get(key).map(|v| v.as_str())
   }
+
+  pub fn env_var(&self, key: &str) -> Option<&str> {
+    let env = &self.req_ctx.server.env;
+
+    env.get(key).map(|v| v.as_str())
+  }
+
+  /// Metadata of the incoming request: its `method`, `path` and the caller's `ip`.
+  pub fn request(&self, key: &str) -> Option<String> {
+    match key {
+      "method" => self.req_ctx.req_method.as_ref().map(ToString::to_string),
+      "path" => self.req_ctx.req_path.clone(),
+      "ip" => self.req_ctx.req_ip.as_ref().map(ToString::to_string),
+      _ => None,
+    }
+  }
+
+  pub fn operation(&self, key: &str) -> Option<&str> {
+    match key {
+      "name" => self.req_ctx.operation_name.as_deref(),
+      _ => None,
+    }
+  }
 }
 
 fn get_path_value<'a, T: AsRef<str>>(input: &'a Value, path: &[T]) -> Option<&'a Value> {
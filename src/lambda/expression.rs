@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
@@ -5,18 +6,21 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use async_graphql::dataloader::{DataLoader, NoCache};
+use async_graphql::Name;
+use indexmap::IndexMap;
 use serde::Serialize;
 use serde_json::Value;
 use thiserror::Error;
 
-use super::ResolverContextLike;
+use super::{Resolver, ResolverContextLike};
 use crate::config::group_by::GroupBy;
-use crate::http::{max_age, DefaultHttpClient, HttpDataLoader};
-#[cfg(feature = "unsafe-js")]
-use crate::javascript;
+use crate::config::Transform;
+use crate::http::{max_age, HttpDataLoader};
 use crate::json::JsonLike;
 use crate::lambda::EvaluationContext;
+use crate::path_string::PathString;
 use crate::request_template::RequestTemplate;
+use crate::wasm::WasmPlugin;
 
 #[derive(Clone, Debug)]
 pub enum Expression {
@@ -25,12 +29,47 @@ pub enum Expression {
   EqualTo(Box<Expression>, Box<Expression>),
   Unsafe(Operation),
   Input(Box<Expression>, Vec<String>),
+  If {
+    cond: Box<Expression>,
+    then: Box<Expression>,
+    els: Box<Expression>,
+  },
+  And(Vec<Expression>),
+  Or(Vec<Expression>),
+  Not(Box<Expression>),
+  Gt(Box<Expression>, Box<Expression>),
+  Gte(Box<Expression>, Box<Expression>),
+  Lt(Box<Expression>, Box<Expression>),
+  Lte(Box<Expression>, Box<Expression>),
+  Contains(Box<Expression>, Box<Expression>),
+  IsNull(Box<Expression>),
+  Math(Math, Box<Expression>, Box<Expression>),
+  Concat(Vec<Expression>),
+  Object(Vec<(String, Expression)>),
+  List(Vec<Expression>),
+  /// Evaluates the expressions in order, each one getting the previous one's output as its `value`.
+  Pipe(Vec<Expression>),
+  /// Reshapes the output of an expression, eg. the body an endpoint returns.
+  Transform(Box<Expression>, Transform),
 }
 
 #[derive(Clone, Debug)]
 pub enum Context {
   Value,
   Path(Vec<String>),
+  Args(Vec<String>),
+  Var(String),
+  Header(String),
+  /// Any other template path, eg. `env.API_KEY`, looked up as it would be in a template.
+  Template(Vec<String>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Math {
+  Add,
+  Sub,
+  Mul,
+  Div,
 }
 
 #[derive(Clone)]
@@ -38,9 +77,12 @@ pub enum Operation {
   Endpoint(
     RequestTemplate,
     Option<GroupBy>,
-    Option<Arc<DataLoader<HttpDataLoader<DefaultHttpClient>, NoCache>>>,
+    Option<Arc<DataLoader<HttpDataLoader, NoCache>>>,
   ),
   JS(Box<Expression>, String),
+  Wasm(Box<Expression>, Arc<WasmPlugin>),
+  /// A resolver registered by name, which is assigned once the server is built.
+  Rust(String, Option<Arc<dyn Resolver>>),
 }
 
 impl Debug for Operation {
@@ -57,6 +99,16 @@ impl Debug for Operation {
         .field("input", input)
         .field("script", script)
         .finish(),
+      Operation::Wasm(input, plugin) => f
+        .debug_struct("Wasm")
+        .field("input", input)
+        .field("plugin", plugin)
+        .finish(),
+      Operation::Rust(name, resolver) => f
+        .debug_struct("Rust")
+        .field("name", name)
+        .field("assigned", &resolver.is_some())
+        .finish(),
     }
   }
 }
@@ -71,6 +123,24 @@ pub enum EvaluationError {
 
   #[error("APIValidationError: {0:?}")]
   APIValidationError(Vec<String>),
+
+  #[error("ExprEvalError: {0}")]
+  ExprEvalError(String),
+
+  #[error("WasmException: {0}")]
+  WasmException(String),
+
+  #[error("ResolverError: {0}")]
+  ResolverError(String),
+
+  #[error("DecodeError: {0}")]
+  DecodeError(String),
+}
+
+impl From<crate::http::DecodeError> for EvaluationError {
+  fn from(error: crate::http::DecodeError) -> Self {
+    EvaluationError::DecodeError(error.to_string())
+  }
 }
 
 impl<'a> From<crate::valid::ValidationError<&'a str>> for EvaluationError {
@@ -89,6 +159,15 @@ impl Expression {
         Expression::Context(op) => match op {
           Context::Value => Ok(ctx.value().cloned().unwrap_or(async_graphql::Value::Null)),
           Context::Path(path) => Ok(ctx.path_value(path).cloned().unwrap_or(async_graphql::Value::Null)),
+          Context::Args(path) => Ok(ctx.arg(path).cloned().unwrap_or(async_graphql::Value::Null)),
+          Context::Var(name) => Ok(ctx.var(name).map(async_graphql::Value::from).unwrap_or_default()),
+          Context::Header(name) => Ok(ctx.header(name).map(async_graphql::Value::from).unwrap_or_default()),
+          Context::Template(path) => Ok(
+            ctx
+              .path_json(path)
+              .and_then(|value| async_graphql::Value::from_json(value).ok())
+              .unwrap_or_default(),
+          ),
         },
         Expression::Input(input, path) => {
           let inp = &input.eval(ctx).await?;
@@ -98,13 +177,104 @@ impl Expression {
         Expression::EqualTo(left, right) => Ok(async_graphql::Value::from(
           left.eval(ctx).await? == right.eval(ctx).await?,
         )),
+        Expression::If { cond, then, els } => {
+          if is_truthy(&cond.eval(ctx).await?) {
+            then.eval(ctx).await
+          } else {
+            els.eval(ctx).await
+          }
+        }
+        Expression::And(exprs) => {
+          for expr in exprs {
+            if !is_truthy(&expr.eval(ctx).await?) {
+              return Ok(async_graphql::Value::from(false));
+            }
+          }
+          Ok(async_graphql::Value::from(true))
+        }
+        Expression::Or(exprs) => {
+          for expr in exprs {
+            if is_truthy(&expr.eval(ctx).await?) {
+              return Ok(async_graphql::Value::from(true));
+            }
+          }
+          Ok(async_graphql::Value::from(false))
+        }
+        Expression::Not(expr) => Ok(async_graphql::Value::from(!is_truthy(&expr.eval(ctx).await?))),
+        Expression::Gt(left, right) => Ok(async_graphql::Value::from(
+          compare(&left.eval(ctx).await?, &right.eval(ctx).await?)?.is_gt(),
+        )),
+        Expression::Gte(left, right) => Ok(async_graphql::Value::from(
+          compare(&left.eval(ctx).await?, &right.eval(ctx).await?)?.is_ge(),
+        )),
+        Expression::Lt(left, right) => Ok(async_graphql::Value::from(
+          compare(&left.eval(ctx).await?, &right.eval(ctx).await?)?.is_lt(),
+        )),
+        Expression::Lte(left, right) => Ok(async_graphql::Value::from(
+          compare(&left.eval(ctx).await?, &right.eval(ctx).await?)?.is_le(),
+        )),
+        Expression::Contains(container, item) => {
+          let item = item.eval(ctx).await?;
+          let contains = match container.eval(ctx).await? {
+            async_graphql::Value::List(list) => list.contains(&item),
+            async_graphql::Value::Object(map) => {
+              matches!(&item, async_graphql::Value::String(key) if map.contains_key(key.as_str()))
+            }
+            async_graphql::Value::String(s) => {
+              matches!(&item, async_graphql::Value::String(sub) if s.contains(sub.as_str()))
+            }
+            _ => false,
+          };
+          Ok(async_graphql::Value::from(contains))
+        }
+        Expression::IsNull(expr) => Ok(async_graphql::Value::from(matches!(
+          expr.eval(ctx).await?,
+          async_graphql::Value::Null
+        ))),
+        Expression::Math(op, left, right) => Ok(op.apply(&left.eval(ctx).await?, &right.eval(ctx).await?)?),
+        Expression::Concat(exprs) => {
+          let mut result = String::new();
+          for expr in exprs {
+            match expr.eval(ctx).await? {
+              async_graphql::Value::String(s) => result.push_str(&s),
+              async_graphql::Value::Null => {}
+              value => result.push_str(&value.into_json()?.to_string()),
+            }
+          }
+          Ok(async_graphql::Value::String(result))
+        }
+        Expression::Object(fields) => {
+          let mut map = IndexMap::new();
+          for (name, expr) in fields {
+            map.insert(Name::new(name), expr.eval(ctx).await?);
+          }
+          Ok(async_graphql::Value::Object(map))
+        }
+        Expression::Pipe(steps) => {
+          let mut value = ctx.value().cloned().unwrap_or_default();
+          for step in steps {
+            let graphql_ctx =
+              PipeContext { value: &value, args: ctx.graphql_ctx.args(), parent: ctx.graphql_ctx.parent() };
+            let step_ctx = EvaluationContext { req_ctx: ctx.req_ctx, graphql_ctx: &graphql_ctx, timeout: ctx.timeout };
+            value = step.eval(&step_ctx).await?;
+          }
+          Ok(value)
+        }
+        Expression::Transform(expr, transform) => Ok(transform.apply(expr.eval(ctx).await?)),
+        Expression::List(exprs) => {
+          let mut list = Vec::with_capacity(exprs.len());
+          for expr in exprs {
+            list.push(expr.eval(ctx).await?);
+          }
+          Ok(async_graphql::Value::List(list))
+        }
         Expression::Unsafe(operation) => {
           match operation {
-            Operation::Endpoint(req_template, _, dl) => {
+            Operation::Endpoint(req_template, group_by, dl) => {
               let req = req_template.to_request(ctx)?;
               let is_get = req.method() == reqwest::Method::GET;
-              // Attempt to short circuit GET request
-              if is_get && ctx.req_ctx.upstream.batch.is_some() {
+              // Attempt to short circuit GET and batched requests
+              if (is_get || group_by.is_some()) && ctx.req_ctx.upstream.batch.is_some() {
                 let headers = ctx
                   .req_ctx
                   .upstream
@@ -113,13 +283,20 @@ impl Expression {
                   .map(|s| s.headers)
                   .unwrap_or_default();
                 let endpoint_key = crate::http::DataLoaderRequest::new(req, headers);
-                let resp = dl
-                  .as_ref()
-                  .unwrap()
-                  .load_one(endpoint_key)
+                let dl = dl.clone().unwrap();
+                let resp = ctx
+                  .req_ctx
+                  .memoize(endpoint_key, |key| async move {
+                    dl.load_one(key)
+                      .await
+                      .map_err(|e| anyhow::anyhow!("{:#}", e))?
+                      .unwrap_or_else(|| Ok(Default::default()))
+                      .map_err(|e| anyhow::anyhow!("{:#}", e))
+                  })
                   .await
-                  .map_err(|e| EvaluationError::IOException(e.to_string()))?
-                  .unwrap_or_default();
+                  .map_err(|e| EvaluationError::IOException(format!("{:#}", e)))?
+                  .decode(req_template.endpoint.response_format.as_ref())
+                  .map_err(EvaluationError::from)?;
                 if ctx.req_ctx.server.get_enable_cache_control() && resp.status.is_success() {
                   if let Some(max_age) = max_age(&resp) {
                     ctx.req_ctx.set_min_max_age(max_age.as_secs());
@@ -133,7 +310,9 @@ impl Expression {
                 .req_ctx
                 .execute(req)
                 .await
-                .map_err(|e| EvaluationError::IOException(e.to_string()))?;
+                .map_err(|e| EvaluationError::IOException(format!("{:#}", e)))?
+                .decode(req_template.endpoint.response_format.as_ref())
+                .map_err(EvaluationError::from)?;
               if ctx.req_ctx.server.get_enable_http_validation() {
                 req_template
                   .endpoint
@@ -161,14 +340,105 @@ impl Expression {
               #[cfg(feature = "unsafe-js")]
               {
                 let input = input.eval(ctx).await?;
-                result = javascript::execute_js(script, input, Some(ctx.timeout))
+                let runtime = ctx.req_ctx.js_runtime.as_ref().ok_or(EvaluationError::JSException(
+                  "JS runtime is not initialized".to_string(),
+                ))?;
+                result = runtime
+                  .execute(script, input, Some(ctx.timeout))
+                  .await
                   .map_err(|e| EvaluationError::JSException(e.to_string()).into());
               }
               result
             }
+            Operation::Wasm(input, plugin) => {
+              let input = input.eval(ctx).await?;
+              let plugin = plugin.clone();
+              // Fuel bounds the time a call can take, but it's still CPU bound work.
+              tokio::task::spawn_blocking(move || plugin.call(&input))
+                .await?
+                .map_err(|e| EvaluationError::WasmException(e.to_string()).into())
+            }
+            Operation::Rust(name, resolver) => {
+              let resolver = resolver.as_ref().ok_or(EvaluationError::ResolverError(format!(
+                "resolver '{}' is not registered",
+                name
+              )))?;
+              resolver.resolve(&ctx.erased()).await
+            }
           }
         }
       }
     })
   }
 }
+
+/// Only `null` and `false` are falsy.
+fn is_truthy(value: &async_graphql::Value) -> bool {
+  !matches!(value, async_graphql::Value::Null | async_graphql::Value::Boolean(false))
+}
+
+fn compare(left: &async_graphql::Value, right: &async_graphql::Value) -> Result<Ordering> {
+  let ordering = match (left, right) {
+    (async_graphql::Value::Number(l), async_graphql::Value::Number(r)) => match (l.as_i64(), r.as_i64()) {
+      (Some(l), Some(r)) => Some(l.cmp(&r)),
+      _ => l.as_f64().zip(r.as_f64()).and_then(|(l, r)| l.partial_cmp(&r)),
+    },
+    (async_graphql::Value::String(l), async_graphql::Value::String(r)) => Some(l.cmp(r)),
+    _ => None,
+  };
+  ordering.ok_or_else(|| EvaluationError::ExprEvalError(format!("Unable to compare {} with {}", left, right)).into())
+}
+
+impl Math {
+  fn apply(&self, left: &async_graphql::Value, right: &async_graphql::Value) -> Result<async_graphql::Value> {
+    let (async_graphql::Value::Number(l), async_graphql::Value::Number(r)) = (left, right) else {
+      return Err(EvaluationError::ExprEvalError(format!("Expected numbers, found {} and {}", left, right)).into());
+    };
+
+    // Stay within integers as long as the result is exact
+    if let (Some(l), Some(r)) = (l.as_i64(), r.as_i64()) {
+      let result = match self {
+        Math::Add => l.checked_add(r),
+        Math::Sub => l.checked_sub(r),
+        Math::Mul => l.checked_mul(r),
+        Math::Div => l.checked_rem(r).filter(|rem| *rem == 0).and_then(|_| l.checked_div(r)),
+      };
+      if let Some(result) = result {
+        return Ok(async_graphql::Value::from(result));
+      }
+    }
+
+    let (l, r) = l.as_f64().zip(r.as_f64()).unwrap_or_default();
+    let result = match self {
+      Math::Add => l + r,
+      Math::Sub => l - r,
+      Math::Mul => l * r,
+      Math::Div if r == 0.0 => return Err(EvaluationError::ExprEvalError("Division by zero".to_string()).into()),
+      Math::Div => l / r,
+    };
+    async_graphql::Number::from_f64(result)
+      .map(async_graphql::Value::Number)
+      .ok_or_else(|| EvaluationError::ExprEvalError(format!("Invalid result {}", result)).into())
+  }
+}
+
+/// Resolver context of a step of a pipe, which sees the previous step's output as its `value`.
+struct PipeContext<'a> {
+  value: &'a async_graphql::Value,
+  args: Option<&'a IndexMap<Name, async_graphql::Value>>,
+  parent: Option<&'a async_graphql::Value>,
+}
+
+impl<'a> ResolverContextLike<'a> for PipeContext<'a> {
+  fn value(&'a self) -> Option<&'a async_graphql::Value> {
+    Some(self.value)
+  }
+
+  fn args(&'a self) -> Option<&'a IndexMap<Name, async_graphql::Value>> {
+    self.args
+  }
+
+  fn parent(&'a self) -> Option<&'a async_graphql::Value> {
+    self.parent
+  }
+}
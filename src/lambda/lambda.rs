@@ -1,8 +1,10 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use super::expression;
 use super::expression::{Context, Expression, Operation};
 use crate::request_template::RequestTemplate;
+use crate::wasm::WasmPlugin;
 
 #[derive(Clone)]
 pub struct Lambda<A> {
@@ -22,15 +24,66 @@ impl<A> Lambda<A> {
     Lambda::new(Expression::EqualTo(self.box_expr(), Box::new(other.expression)))
   }
 
+  pub fn gt(self, other: Self) -> Lambda<bool> {
+    Lambda::new(Expression::Gt(self.box_expr(), other.box_expr()))
+  }
+
+  pub fn gte(self, other: Self) -> Lambda<bool> {
+    Lambda::new(Expression::Gte(self.box_expr(), other.box_expr()))
+  }
+
+  pub fn lt(self, other: Self) -> Lambda<bool> {
+    Lambda::new(Expression::Lt(self.box_expr(), other.box_expr()))
+  }
+
+  pub fn lte(self, other: Self) -> Lambda<bool> {
+    Lambda::new(Expression::Lte(self.box_expr(), other.box_expr()))
+  }
+
+  pub fn contains<B>(self, item: Lambda<B>) -> Lambda<bool> {
+    Lambda::new(Expression::Contains(self.box_expr(), item.box_expr()))
+  }
+
+  pub fn is_null(self) -> Lambda<bool> {
+    Lambda::new(Expression::IsNull(self.box_expr()))
+  }
+
   pub fn to_unsafe_js(self, script: String) -> Lambda<serde_json::Value> {
     Lambda::new(Expression::Unsafe(Operation::JS(self.box_expr(), script)))
   }
 
+  pub fn to_wasm(self, plugin: Arc<WasmPlugin>) -> Lambda<serde_json::Value> {
+    Lambda::new(Expression::Unsafe(Operation::Wasm(self.box_expr(), plugin)))
+  }
+
   pub fn to_input_path(self, path: Vec<String>) -> Lambda<serde_json::Value> {
     Lambda::new(Expression::Input(self.box_expr(), path))
   }
 }
 
+impl Lambda<bool> {
+  pub fn and(self, other: Self) -> Self {
+    Lambda::new(Expression::And(vec![self.expression, other.expression]))
+  }
+
+  pub fn or(self, other: Self) -> Self {
+    Lambda::new(Expression::Or(vec![self.expression, other.expression]))
+  }
+
+  /// Only the selected branch gets evaluated.
+  pub fn if_then_else<B>(self, then: Lambda<B>, els: Lambda<B>) -> Lambda<B> {
+    Lambda::new(Expression::If { cond: self.box_expr(), then: then.box_expr(), els: els.box_expr() })
+  }
+}
+
+impl std::ops::Not for Lambda<bool> {
+  type Output = Self;
+
+  fn not(self) -> Self {
+    Lambda::new(Expression::Not(self.box_expr()))
+  }
+}
+
 impl Lambda<serde_json::Value> {
   pub fn context() -> Self {
     Lambda::new(Expression::Context(expression::Context::Value))
@@ -70,7 +123,7 @@ mod tests {
 
   use crate::endpoint::Endpoint;
   use crate::http::RequestContext;
-  use crate::lambda::{EmptyResolverContext, EvaluationContext, Lambda};
+  use crate::lambda::{EmptyResolverContext, EvaluationContext, Expression, Lambda, Math};
   use crate::request_template::RequestTemplate;
 
   impl<B> Lambda<B>
@@ -100,6 +153,103 @@ mod tests {
     assert!(!result)
   }
 
+  #[tokio::test]
+  async fn test_comparisons() {
+    assert!(Lambda::from(2.0).gt(Lambda::from(1.0)).eval().await.unwrap());
+    assert!(Lambda::from(1.0).gte(Lambda::from(1.0)).eval().await.unwrap());
+    assert!(Lambda::from("a").lt(Lambda::from("b")).eval().await.unwrap());
+    assert!(!Lambda::from(2.0).lte(Lambda::from(1.0)).eval().await.unwrap());
+    assert!(Lambda::from(json!(1))
+      .gt(Lambda::from(json!("a")))
+      .eval()
+      .await
+      .is_err());
+  }
+
+  #[tokio::test]
+  async fn test_logical_operators() {
+    let t = || Lambda::from(true);
+    let f = || Lambda::from(false);
+    assert!(t().and(t()).eval().await.unwrap());
+    assert!(!t().and(f()).eval().await.unwrap());
+    assert!(f().or(t()).eval().await.unwrap());
+    assert!(!f().or(f()).eval().await.unwrap());
+    assert!((!f()).eval().await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_logical_operators_short_circuit() {
+    let division_by_zero = || {
+      Expression::Math(
+        Math::Div,
+        Box::new(Lambda::from(1).expression),
+        Box::new(Lambda::from(0).expression),
+      )
+    };
+    assert!(Lambda::<bool>::new(division_by_zero()).eval().await.is_err());
+    assert!(!Lambda::from(false)
+      .and(Lambda::new(division_by_zero()))
+      .eval()
+      .await
+      .unwrap());
+    assert!(Lambda::from(true)
+      .or(Lambda::new(division_by_zero()))
+      .eval()
+      .await
+      .unwrap());
+    let result = Lambda::from(true)
+      .if_then_else(Lambda::from(1.0), Lambda::new(division_by_zero()))
+      .eval()
+      .await;
+    assert_eq!(result.unwrap(), 1.0);
+  }
+
+  #[tokio::test]
+  async fn test_contains_and_is_null() {
+    let list = Lambda::from(json!([1, 2, 3]));
+    assert!(list.clone().contains(Lambda::from(2)).eval().await.unwrap());
+    assert!(!list.contains(Lambda::from(4)).eval().await.unwrap());
+    assert!(Lambda::from("tailcall")
+      .contains(Lambda::from("call"))
+      .eval()
+      .await
+      .unwrap());
+    assert!(Lambda::from(json!({"a": 1}))
+      .contains(Lambda::from("a"))
+      .eval()
+      .await
+      .unwrap());
+    assert!(Lambda::context_field("missing".to_string())
+      .is_null()
+      .eval()
+      .await
+      .unwrap());
+    assert!(!Lambda::from(0).is_null().eval().await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_if_falls_back_to_literal() {
+    let name = || Lambda::context_field("name".to_string());
+    let result = name()
+      .is_null()
+      .if_then_else(Lambda::from(json!("anonymous")), name())
+      .eval()
+      .await
+      .unwrap();
+    assert_eq!(result, json!("anonymous"));
+  }
+
+  #[tokio::test]
+  async fn test_pipe() {
+    let steps = vec![
+      Lambda::from(json!({"user": {"id": 1}})).expression,
+      Lambda::context_field("user".to_string()).expression,
+      Lambda::context_field("id".to_string()).expression,
+    ];
+    let result = Lambda::<serde_json::Value>::new(Expression::Pipe(steps)).eval().await;
+    assert_eq!(result.unwrap(), json!(1));
+  }
+
   #[tokio::test]
   async fn test_endpoint() {
     let server = MockServer::start();
@@ -121,7 +271,11 @@ mod tests {
   #[cfg(feature = "unsafe-js")]
   #[tokio::test]
   async fn test_unsafe_js() {
-    let result = Lambda::from(1.0).to_unsafe_js("ctx + 100".to_string()).eval().await;
+    let script = "ctx + 100".to_string();
+    let runtime = crate::javascript::Runtime::new(vec![script.clone()], 1);
+    let req_ctx = RequestContext::default().js_runtime(std::sync::Arc::new(runtime));
+    let ctx = EvaluationContext::new(&req_ctx, &EmptyResolverContext);
+    let result = Lambda::from(1.0).to_unsafe_js(script).expression.eval(&ctx).await;
     let f64 = result.unwrap().as_f64().unwrap();
     assert_eq!(f64, 101.0)
   }
@@ -1,9 +1,11 @@
 mod evaluation_context;
 mod expression;
 mod lambda;
+mod resolver;
 mod resolver_context_like;
 
 pub use evaluation_context::EvaluationContext;
-pub use expression::{Expression, Operation};
+pub use expression::{Context, Expression, Math, Operation};
 pub use lambda::Lambda;
-pub use resolver_context_like::{EmptyResolverContext, ResolverContextLike};
+pub use resolver::{DynResolverContext, Resolver, Resolvers};
+pub use resolver_context_like::{EmptyResolverContext, ObjectValue, ResolverContextLike, WithArgs};
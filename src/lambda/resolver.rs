@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::{EvaluationContext, ResolverContextLike};
+
+pub type DynResolverContext<'a> = dyn ResolverContextLike<'a> + Send + Sync + 'a;
+
+/// A resolver implemented in Rust, referenced from the config with `@rust(name: "...")`.
+#[async_trait::async_trait]
+pub trait Resolver: Send + Sync {
+  async fn resolve<'a>(&self, ctx: &EvaluationContext<'a, DynResolverContext<'a>>) -> Result<async_graphql::Value>;
+}
+
+/// The Rust resolvers available to a server, by name.
+#[derive(Clone, Default)]
+pub struct Resolvers(HashMap<String, Arc<dyn Resolver>>);
+
+impl Resolvers {
+  pub fn insert(&mut self, name: impl Into<String>, resolver: impl Resolver + 'static) {
+    self.0.insert(name.into(), Arc::new(resolver));
+  }
+
+  pub fn get(&self, name: &str) -> Option<Arc<dyn Resolver>> {
+    self.0.get(name).cloned()
+  }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use async_graphql::dynamic::ResolverContext;
 use async_graphql::{Name, Value};
 use indexmap::IndexMap;
@@ -5,6 +7,38 @@ use indexmap::IndexMap;
 pub trait ResolverContextLike<'a> {
   fn value(&'a self) -> Option<&'a Value>;
   fn args(&'a self) -> Option<&'a IndexMap<Name, Value>>;
+
+  /// The value of the object that the current object was resolved from.
+  fn parent(&'a self) -> Option<&'a Value> {
+    None
+  }
+}
+
+/// An object along with the value of the object it was resolved from,
+/// so that its fields can refer to it as `parent`.
+pub struct ObjectValue {
+  pub value: Arc<Value>,
+  pub parent: Option<Arc<Value>>,
+}
+
+/// A context with its arguments replaced, eg. by the values upstreams expect for them.
+pub struct WithArgs<'a, Ctx: ?Sized> {
+  pub ctx: &'a Ctx,
+  pub args: IndexMap<Name, Value>,
+}
+
+impl<'a, Ctx: ResolverContextLike<'a> + ?Sized> ResolverContextLike<'a> for WithArgs<'a, Ctx> {
+  fn value(&'a self) -> Option<&'a Value> {
+    self.ctx.value()
+  }
+
+  fn args(&'a self) -> Option<&'a IndexMap<Name, Value>> {
+    Some(&self.args)
+  }
+
+  fn parent(&'a self) -> Option<&'a Value> {
+    self.ctx.parent()
+  }
 }
 
 pub struct EmptyResolverContext;
@@ -21,10 +55,17 @@ impl<'a> ResolverContextLike<'a> for EmptyResolverContext {
 
 impl<'a> ResolverContextLike<'a> for ResolverContext<'a> {
   fn value(&'a self) -> Option<&'a Value> {
-    self.parent_value.as_value()
+    self
+      .parent_value
+      .as_value()
+      .or_else(|| Some(self.parent_value.downcast_ref::<ObjectValue>()?.value.as_ref()))
   }
 
   fn args(&'a self) -> Option<&'a IndexMap<Name, Value>> {
     Some(self.args.as_index_map())
   }
+
+  fn parent(&'a self) -> Option<&'a Value> {
+    self.parent_value.downcast_ref::<ObjectValue>()?.parent.as_deref()
+  }
 }
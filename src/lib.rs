@@ -1,6 +1,7 @@
 #![allow(clippy::module_inception)]
 pub mod async_graphql_hyper;
 pub mod blueprint;
+pub mod builder;
 pub mod cache;
 pub mod cli;
 pub mod config;
@@ -8,14 +9,18 @@ pub mod directive;
 pub mod document;
 pub mod endpoint;
 pub mod has_headers;
+pub mod has_uploads;
 pub mod http;
 #[cfg(feature = "unsafe-js")]
 pub mod javascript;
 pub mod json;
+pub mod json_template;
 pub mod lambda;
 pub mod mustache;
 pub mod path_string;
 pub mod print_schema;
 pub mod request_template;
+pub mod scalar;
 pub mod try_fold;
 pub mod valid;
+pub mod wasm;
@@ -1,3 +1,4 @@
+use base64::Engine;
 use nom::{Finish, IResult};
 
 use crate::path_string::PathString;
@@ -9,6 +10,69 @@ pub struct Mustache(Vec<Segment>);
 pub enum Segment {
   Literal(String),
   Expression(Vec<String>),
+  /// An expression piped through filters, eg. `{{args.name | upper}}`.
+  Filtered(Vec<String>, Vec<Filter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+  UrlEncode,
+  Default(String),
+  Upper,
+  Json,
+  Base64,
+  Join(String),
+}
+
+impl Filter {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Filter::UrlEncode => "urlencode",
+      Filter::Default(_) => "default",
+      Filter::Upper => "upper",
+      Filter::Json => "json",
+      Filter::Base64 => "base64",
+      Filter::Join(_) => "join",
+    }
+  }
+
+  fn apply(&self, value: Option<serde_json::Value>) -> Option<serde_json::Value> {
+    match self {
+      Filter::Default(default) => match value {
+        None | Some(serde_json::Value::Null) => Some(serde_json::Value::String(default.clone())),
+        value => value,
+      },
+      Filter::Json => Some(serde_json::Value::String(value.unwrap_or_default().to_string())),
+      Filter::Upper => map_string(value, |s| s.to_uppercase()),
+      Filter::UrlEncode => map_string(value, |s| {
+        url::form_urlencoded::byte_serialize(s.as_bytes())
+          .collect::<String>()
+          .replace('+', "%20")
+      }),
+      Filter::Base64 => map_string(value, |s| base64::engine::general_purpose::STANDARD.encode(s)),
+      Filter::Join(separator) => match value {
+        Some(serde_json::Value::Array(items)) => Some(serde_json::Value::String(
+          items.iter().filter_map(to_string).collect::<Vec<_>>().join(separator),
+        )),
+        value => value,
+      },
+    }
+  }
+}
+
+fn to_string(value: &serde_json::Value) -> Option<String> {
+  match value {
+    serde_json::Value::Null => None,
+    serde_json::Value::String(s) => Some(s.clone()),
+    value => Some(value.to_string()),
+  }
+}
+
+fn map_string(value: Option<serde_json::Value>, f: impl Fn(&str) -> String) -> Option<serde_json::Value> {
+  value
+    .as_ref()
+    .and_then(to_string)
+    .map(|s| serde_json::Value::String(f(&s)))
 }
 
 impl From<Vec<Segment>> for Mustache {
@@ -22,7 +86,7 @@ impl Mustache {
     match self {
       Mustache(segments) => {
         for s in segments {
-          if let Segment::Expression(_) = s {
+          if let Segment::Expression(_) | Segment::Filtered(_, _) = s {
             return false;
           }
         }
@@ -31,11 +95,17 @@ impl Mustache {
     }
   }
 
+  /// Parses a template, failing on any `{{` that doesn't start a valid expression.
   pub fn parse(str: &str) -> anyhow::Result<Mustache> {
-    let result = parse_mustache(str).finish();
-    match result {
+    if str.is_empty() {
+      return Ok(Mustache::from(vec![Segment::Literal(String::new())]));
+    }
+    match nom::combinator::all_consuming(parse_mustache)(str).finish() {
       Ok((_, mustache)) => Ok(mustache),
-      Err(_) => Ok(Mustache::from(vec![Segment::Literal(str.to_string())])),
+      Err(e) => {
+        let expression = e.input.find("}}").map_or(e.input, |end| &e.input[..end + 2]);
+        Err(anyhow::anyhow!("invalid template expression '{}'", expression))
+      }
     }
   }
 
@@ -46,17 +116,73 @@ impl Mustache {
         .map(|segment| match segment {
           Segment::Literal(text) => text.clone(),
           Segment::Expression(parts) => value.path_string(parts).map(|a| a.to_string()).unwrap_or_default(),
+          Segment::Filtered(parts, filters) => filters
+            .iter()
+            .fold(value.path_json(parts), |value, filter| filter.apply(value))
+            .as_ref()
+            .and_then(to_string)
+            .unwrap_or_default(),
         })
         .collect(),
     }
   }
 
-  pub fn expression_segments(&self) -> Vec<&Vec<String>> {
+  /// Renders a template that is a single expression to the value it points to, keeping its JSON type, and any
+  /// other template to a string.
+  pub fn render_json(&self, value: &impl PathString) -> serde_json::Value {
+    match self.segments() {
+      [Segment::Expression(parts)] => value.path_json(parts),
+      [Segment::Filtered(parts, filters)] => filters
+        .iter()
+        .fold(value.path_json(parts), |value, filter| filter.apply(value)),
+      _ => Some(serde_json::Value::String(self.render(value))),
+    }
+    .unwrap_or_default()
+  }
+
+  pub fn segments(&self) -> &[Segment] {
+    &self.0
+  }
+
+  /// Replaces each expression for which `f` returns segments, leaving the others as they are.
+  /// A filtered expression can only be replaced by a single expression, or by literals that the filters are
+  /// applied to right away.
+  pub fn try_substitute<E: From<String>>(
+    &self,
+    f: &impl Fn(&[String]) -> Result<Option<Vec<Segment>>, E>,
+  ) -> Result<Mustache, E> {
+    let mut segments = Vec::with_capacity(self.0.len());
+    for segment in &self.0 {
+      match segment {
+        Segment::Expression(parts) => match f(parts)? {
+          Some(replacement) => segments.extend(replacement),
+          None => segments.push(segment.clone()),
+        },
+        Segment::Filtered(parts, filters) => match f(parts)?.as_deref() {
+          Some([Segment::Expression(parts)]) => segments.push(Segment::Filtered(parts.clone(), filters.clone())),
+          Some(replacement) if Mustache(replacement.to_vec()).is_const() => {
+            let text = Mustache(replacement.to_vec()).render(&serde_json::Value::Null);
+            let value = (!replacement.is_empty()).then_some(serde_json::Value::String(text));
+            let value = filters.iter().fold(value, |value, filter| filter.apply(value));
+            segments.push(Segment::Literal(value.as_ref().and_then(to_string).unwrap_or_default()));
+          }
+          Some(_) => return Err(E::from(format!("can't apply filters to '{}'", parts.join(".")))),
+          None => segments.push(segment.clone()),
+        },
+        Segment::Literal(_) => segments.push(segment.clone()),
+      }
+    }
+    Ok(Mustache(segments))
+  }
+
+  /// The path of each expression, along with the filters it's piped through.
+  pub fn expressions(&self) -> Vec<(&Vec<String>, &[Filter])> {
     match self {
       Mustache(segments) => segments
         .iter()
         .filter_map(|seg| match seg {
-          Segment::Expression(parts) => Some(parts),
+          Segment::Expression(parts) => Some((parts, [].as_slice())),
+          Segment::Filtered(parts, filters) => Some((parts, filters.as_slice())),
           _ => None,
         })
         .collect(),
@@ -64,36 +190,141 @@ impl Mustache {
   }
 }
 
+/// A name starts with a letter or an underscore, and may contain digits, underscores and hyphens.
 fn parse_name(input: &str) -> IResult<&str, String> {
+  nom::combinator::map(
+    nom::combinator::recognize(nom::sequence::pair(
+      nom::branch::alt((nom::character::complete::alpha1, nom::bytes::complete::tag("_"))),
+      nom::multi::many0_count(nom::branch::alt((
+        nom::character::complete::alphanumeric1,
+        nom::bytes::complete::tag("_"),
+        nom::bytes::complete::tag("-"),
+      ))),
+    )),
+    String::from,
+  )(input)
+}
+
+fn parse_index(input: &str) -> IResult<&str, String> {
+  nom::combinator::map(nom::character::complete::digit1, String::from)(input)
+}
+
+/// A key in brackets, eg. `["content-type"]`, `['user id']` or `[0]`.
+fn parse_bracket(input: &str) -> IResult<&str, String> {
+  let single_quoted = nom::combinator::map(
+    nom::sequence::delimited(
+      nom::character::complete::char('\''),
+      nom::bytes::complete::take_while(|c| c != '\''),
+      nom::character::complete::char('\''),
+    ),
+    String::from,
+  );
+  nom::sequence::delimited(
+    nom::sequence::pair(
+      nom::character::complete::char('['),
+      nom::character::complete::multispace0,
+    ),
+    nom::branch::alt((parse_string, single_quoted, parse_index)),
+    nom::sequence::pair(
+      nom::character::complete::multispace0,
+      nom::character::complete::char(']'),
+    ),
+  )(input)
+}
+
+fn parse_path(input: &str) -> IResult<&str, Vec<String>> {
+  let dot = nom::sequence::delimited(
+    nom::character::complete::multispace0,
+    nom::character::complete::char('.'),
+    nom::character::complete::multispace0,
+  );
+  let part = nom::branch::alt((
+    nom::sequence::preceded(dot, nom::branch::alt((parse_name, parse_index))),
+    nom::sequence::preceded(nom::character::complete::multispace0, parse_bracket),
+  ));
   nom::combinator::map(
     nom::sequence::tuple((
       nom::character::complete::multispace0,
-      nom::character::complete::alpha1,
-      nom::character::complete::alphanumeric0,
+      parse_name,
+      nom::multi::many0(part),
       nom::character::complete::multispace0,
     )),
-    |(_, a, b, _)| format!("{}{}", a, b),
+    |(_, head, tail, _)| std::iter::once(head).chain(tail).collect(),
+  )(input)
+}
+
+fn parse_string(input: &str) -> IResult<&str, String> {
+  nom::combinator::map(
+    nom::sequence::delimited(
+      nom::character::complete::char('"'),
+      nom::bytes::complete::take_while(|c| c != '"'),
+      nom::character::complete::char('"'),
+    ),
+    String::from,
+  )(input)
+}
+
+fn parse_filter(input: &str) -> IResult<&str, Filter> {
+  let argument = nom::sequence::delimited(
+    nom::sequence::pair(
+      nom::character::complete::char('('),
+      nom::character::complete::multispace0,
+    ),
+    parse_string,
+    nom::sequence::pair(
+      nom::character::complete::multispace0,
+      nom::character::complete::char(')'),
+    ),
+  );
+  nom::combinator::map_opt(
+    nom::sequence::delimited(
+      nom::sequence::pair(
+        nom::character::complete::char('|'),
+        nom::character::complete::multispace0,
+      ),
+      nom::sequence::pair(nom::character::complete::alphanumeric1, nom::combinator::opt(argument)),
+      nom::character::complete::multispace0,
+    ),
+    |(name, argument)| match (name, argument) {
+      ("urlencode", None) => Some(Filter::UrlEncode),
+      ("default", Some(value)) => Some(Filter::Default(value)),
+      ("upper", None) => Some(Filter::Upper),
+      ("json", None) => Some(Filter::Json),
+      ("base64", None) => Some(Filter::Base64),
+      ("join", Some(separator)) => Some(Filter::Join(separator)),
+      _ => None,
+    },
   )(input)
 }
 
-fn parse_expression(input: &str) -> IResult<&str, Vec<String>> {
+fn parse_expression(input: &str) -> IResult<&str, Segment> {
   nom::combinator::map(
     nom::sequence::tuple((
       nom::bytes::complete::tag("{{"),
-      nom::multi::separated_list1(nom::character::complete::char('.'), parse_name),
+      parse_path,
+      nom::multi::many0(parse_filter),
       nom::bytes::complete::tag("}}"),
     )),
-    |(_, vec, _)| vec,
+    |(_, parts, filters, _)| {
+      if filters.is_empty() {
+        Segment::Expression(parts)
+      } else {
+        Segment::Filtered(parts, filters)
+      }
+    },
   )(input)
 }
 
 fn parse_segment(input: &str) -> IResult<&str, Segment> {
-  let expression = nom::combinator::map(parse_expression, Segment::Expression);
-  let literal = nom::combinator::map(nom::bytes::complete::take_while1(|c| c != '{'), |r: &str| {
-    Segment::Literal(r.to_string())
-  });
-
-  nom::branch::alt((expression, literal))(input)
+  let literal = nom::combinator::map(
+    nom::combinator::verify(
+      nom::branch::alt((nom::bytes::complete::take_until("{{"), nom::combinator::rest)),
+      |text: &str| !text.is_empty(),
+    ),
+    |text: &str| Segment::Literal(text.to_string()),
+  );
+
+  nom::branch::alt((parse_expression, literal))(input)
 }
 
 fn parse_mustache(input: &str) -> IResult<&str, Mustache> {
@@ -105,7 +336,7 @@ mod tests {
   mod parse {
     use pretty_assertions::assert_eq;
 
-    use crate::mustache::{Mustache, Segment};
+    use crate::mustache::{Filter, Mustache, Segment};
 
     #[test]
     fn test_single_literal() {
@@ -202,14 +433,109 @@ mod tests {
 
     #[test]
     fn test_unfinished_expression() {
-      let s = r"{{hello.world";
-      let mustache: Mustache = Mustache::parse(s).unwrap();
+      let error = Mustache::parse(r"{{hello.world").unwrap_err();
+      assert_eq!(error.to_string(), "invalid template expression '{{hello.world'");
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+      let error = Mustache::parse(r"/users/{{args.1id}}/posts").unwrap_err();
+      assert_eq!(error.to_string(), "invalid template expression '{{args.1id}}'");
+    }
+
+    #[test]
+    fn test_identifiers() {
+      let mustache = Mustache::parse("{{value.user_id}}/{{headers.x-api-key}}/{{_private}}").unwrap();
+      assert_eq!(
+        mustache,
+        Mustache::from(vec![
+          Segment::Expression(vec!["value".to_string(), "user_id".to_string()]),
+          Segment::Literal("/".to_string()),
+          Segment::Expression(vec!["headers".to_string(), "x-api-key".to_string()]),
+          Segment::Literal("/".to_string()),
+          Segment::Expression(vec!["_private".to_string()]),
+        ])
+      );
+    }
+
+    #[test]
+    fn test_indices() {
+      let mustache = Mustache::parse("{{value.items.0.id}}{{ value.items[1] }}").unwrap();
       assert_eq!(
         mustache,
-        Mustache::from(vec![Segment::Literal("{{hello.world".to_string())])
+        Mustache::from(vec![
+          Segment::Expression(vec![
+            "value".to_string(),
+            "items".to_string(),
+            "0".to_string(),
+            "id".to_string()
+          ]),
+          Segment::Expression(vec!["value".to_string(), "items".to_string(), "1".to_string()]),
+        ])
       );
     }
 
+    #[test]
+    fn test_bracket_keys() {
+      let mustache = Mustache::parse(r#"{{headers["content-type"]}} {{value['first name'].length}}"#).unwrap();
+      assert_eq!(
+        mustache,
+        Mustache::from(vec![
+          Segment::Expression(vec!["headers".to_string(), "content-type".to_string()]),
+          Segment::Literal(" ".to_string()),
+          Segment::Expression(vec![
+            "value".to_string(),
+            "first name".to_string(),
+            "length".to_string()
+          ]),
+        ])
+      );
+    }
+
+    #[test]
+    fn test_single_braces() {
+      let s = r#"{"id": {{args.id}}}"#;
+      let mustache = Mustache::parse(s).unwrap();
+      assert_eq!(
+        mustache,
+        Mustache::from(vec![
+          Segment::Literal(r#"{"id": "#.to_string()),
+          Segment::Expression(vec!["args".to_string(), "id".to_string()]),
+          Segment::Literal("}".to_string()),
+        ])
+      );
+    }
+
+    #[test]
+    fn test_filters() {
+      let mustache = Mustache::parse(r#"{{ args.ids | join(",") | urlencode }}/{{args.name|default( "x" )}}"#).unwrap();
+      assert_eq!(
+        mustache,
+        Mustache::from(vec![
+          Segment::Filtered(
+            vec!["args".to_string(), "ids".to_string()],
+            vec![Filter::Join(",".to_string()), Filter::UrlEncode]
+          ),
+          Segment::Literal("/".to_string()),
+          Segment::Filtered(
+            vec!["args".to_string(), "name".to_string()],
+            vec![Filter::Default("x".to_string())]
+          ),
+        ])
+      );
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+      for s in [
+        "{{args.id | unknown}}",
+        "{{args.id | upper(\"x\")}}",
+        "{{args.id | join}}",
+      ] {
+        assert!(Mustache::parse(s).is_err(), "{s}");
+      }
+    }
+
     #[test]
     fn test_new_number() {
       let mustache = Mustache::parse("123").unwrap();
@@ -233,6 +559,21 @@ mod tests {
       assert_eq!(result, "/v1/templates?project-id=123");
     }
 
+    #[test]
+    fn test_render_filters() {
+      let ctx = json!({"args": {"ids": [1, 2, 3], "name": "John Doe", "user": {"id": 1}}});
+      let render = |s: &str| Mustache::parse(s).unwrap().render(&ctx);
+      assert_eq!(render(r#"/users?id={{args.ids | join(",")}}"#), "/users?id=1,2,3");
+      assert_eq!(render("{{args.name | urlencode}}"), "John%20Doe");
+      assert_eq!(render("{{args.name | upper}}"), "JOHN DOE");
+      assert_eq!(render("{{args.name | base64}}"), "Sm9obiBEb2U=");
+      assert_eq!(render("{{args.user | json}}"), r#"{"id":1}"#);
+      assert_eq!(render("{{args.name | json}}"), r#""John Doe""#);
+      assert_eq!(render(r#"{{args.sort | default("asc") | upper}}"#), "ASC");
+      assert_eq!(render(r#"{{args.name | default("asc")}}"#), "John Doe");
+      assert_eq!(render("{{args.sort | upper}}"), "");
+    }
+
     #[test]
     fn test_render_mixed() {
       struct DummyPath;
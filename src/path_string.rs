@@ -7,6 +7,13 @@ use crate::lambda::{EvaluationContext, ResolverContextLike};
 
 pub trait PathString {
   fn path_string<T: AsRef<str>>(&self, path: &[T]) -> Option<Cow<'_, str>>;
+
+  /// The value at `path`, used by templates that filter it as a whole, eg. `{{args.ids | join(",")}}`.
+  fn path_json<T: AsRef<str>>(&self, path: &[T]) -> Option<serde_json::Value> {
+    self
+      .path_string(path)
+      .map(|s| serde_json::Value::String(s.into_owned()))
+  }
 }
 
 impl PathString for serde_json::Value {
@@ -18,6 +25,10 @@ impl PathString for serde_json::Value {
       _ => None,
     })
   }
+
+  fn path_json<T: AsRef<str>>(&self, path: &[T]) -> Option<serde_json::Value> {
+    self.get_path(path).cloned()
+  }
 }
 
 fn convert_value(value: &async_graphql::Value) -> Option<Cow<'_, str>> {
@@ -31,7 +42,7 @@ fn convert_value(value: &async_graphql::Value) -> Option<Cow<'_, str>> {
   }
 }
 
-impl<'a, Ctx: ResolverContextLike<'a>> PathString for EvaluationContext<'a, Ctx> {
+impl<'a, Ctx: ResolverContextLike<'a> + ?Sized> PathString for EvaluationContext<'a, Ctx> {
   fn path_string<T: AsRef<str>>(&self, path: &[T]) -> Option<Cow<'_, str>> {
     let ctx = self;
 
@@ -44,7 +55,174 @@ impl<'a, Ctx: ResolverContextLike<'a>> PathString for EvaluationContext<'a, Ctx>
       "args" => convert_value(ctx.arg(tail)?),
       "headers" => ctx.header(tail[0].as_ref()).map(|v| v.into()),
       "vars" => ctx.var(tail[0].as_ref()).map(|v| v.into()),
+      "env" => ctx.env_var(tail[0].as_ref()).map(|v| v.into()),
+      "request" => ctx.request(tail[0].as_ref()).map(|v| v.into()),
+      "operation" => ctx.operation(tail[0].as_ref()).map(|v| v.into()),
+      "parent" => convert_value(ctx.parent_value(tail)?),
       _ => None,
     })
   }
+
+  fn path_json<T: AsRef<str>>(&self, path: &[T]) -> Option<serde_json::Value> {
+    if path.len() < 2 {
+      return None;
+    }
+
+    let (head, tail) = path.split_first()?;
+    match head.as_ref() {
+      "value" => self.path_value(tail)?.clone().into_json().ok(),
+      "args" => self.arg(tail)?.clone().into_json().ok(),
+      "parent" => self.parent_value(tail)?.clone().into_json().ok(),
+      _ => self
+        .path_string(path)
+        .map(|s| serde_json::Value::String(s.into_owned())),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::borrow::Cow;
+  use std::collections::BTreeMap;
+  use std::sync::Arc;
+
+  use async_graphql::{Name, Value};
+  use indexmap::IndexMap;
+  use once_cell::sync::Lazy;
+
+  use super::PathString;
+  use crate::builder::TailcallBuilder;
+  use crate::config::Config;
+  use crate::http::{MockHttpClient, RequestContext, Response};
+  use crate::lambda::{EvaluationContext, ResolverContextLike};
+
+  static PARENT: Lazy<Value> = Lazy::new(|| Value::from_json(serde_json::json!({"id": 1, "name": "foo"})).unwrap());
+
+  struct Context;
+
+  impl<'a> ResolverContextLike<'a> for Context {
+    fn value(&'a self) -> Option<&'a Value> {
+      None
+    }
+
+    fn args(&'a self) -> Option<&'a IndexMap<Name, Value>> {
+      None
+    }
+
+    fn parent(&'a self) -> Option<&'a Value> {
+      Some(&PARENT)
+    }
+  }
+
+  fn req_ctx() -> RequestContext {
+    let mut req_ctx = RequestContext::default()
+      .req_method(Some(hyper::Method::POST))
+      .req_path(Some("/graphql".to_string()))
+      .req_ip(Some([127, 0, 0, 1].into()))
+      .operation_name(Some("GetUser".to_string()));
+    req_ctx.server.env = BTreeMap::from([("TOKEN".to_string(), "secret".to_string())]);
+    req_ctx
+  }
+
+  #[test]
+  fn test_request_metadata() {
+    let req_ctx = req_ctx();
+    let ctx = EvaluationContext::new(&req_ctx, &Context);
+    assert_eq!(ctx.path_string(&["env", "TOKEN"]), Some(Cow::Borrowed("secret")));
+    assert_eq!(ctx.path_string(&["env", "HOME"]), None);
+    assert_eq!(ctx.path_string(&["request", "method"]), Some(Cow::Borrowed("POST")));
+    assert_eq!(ctx.path_string(&["request", "path"]), Some(Cow::Borrowed("/graphql")));
+    assert_eq!(ctx.path_string(&["request", "ip"]), Some(Cow::Borrowed("127.0.0.1")));
+    assert_eq!(ctx.path_string(&["operation", "name"]), Some(Cow::Borrowed("GetUser")));
+  }
+
+  #[test]
+  fn test_parent() {
+    let req_ctx = req_ctx();
+    let ctx = EvaluationContext::new(&req_ctx, &Context);
+    assert_eq!(ctx.path_string(&["parent", "name"]), Some(Cow::Borrowed("foo")));
+    assert_eq!(ctx.path_json(&["parent", "id"]), Some(serde_json::json!(1)));
+  }
+
+  #[tokio::test]
+  async fn test_parent_template() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+        }
+
+        type Query {
+          post: Post @const(data: {id: 2, userId: 1})
+        }
+
+        type Post {
+          id: Int!
+          userId: Int!
+          user: User @http(path: "/users/{{value.userId}}")
+        }
+
+        type User {
+          id: Int!
+          path: String
+          post: User @http(path: "/posts/{{parent.id}}")
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    let client = MockHttpClient(|req: &reqwest::Request| {
+      let body = serde_json::json!({"id": 1, "path": req.url().path()});
+      Ok(Response::default().body(Value::from_json(body)?))
+    });
+    let server_ctx = TailcallBuilder::new(config).http_client(client).build().unwrap();
+    let req_ctx = Arc::new(RequestContext::from(&server_ctx));
+    let request = async_graphql::Request::new("{ post { user { path post { path } } } }").data(req_ctx);
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.data.into_json().unwrap(),
+      serde_json::json!({"post": {"user": {"path": "/users/1", "post": {"path": "/posts/2"}}}})
+    );
+  }
+
+  #[tokio::test]
+  async fn test_parent_template_in_pipe() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+        }
+
+        type Query {
+          post: Post @const(data: {id: 2, userId: 1})
+        }
+
+        type Post {
+          id: Int!
+          userId: Int!
+          user: User @http(path: "/users/{{value.userId}}")
+        }
+
+        type User {
+          id: Int!
+          path: String
+          post: User @pipe(steps: [{http: {path: "/users/{{value.id}}"}}, {http: {path: "/posts/{{parent.id}}"}}])
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    let client = MockHttpClient(|req: &reqwest::Request| {
+      let body = serde_json::json!({"id": 1, "path": req.url().path()});
+      Ok(Response::default().body(Value::from_json(body)?))
+    });
+    let server_ctx = TailcallBuilder::new(config).http_client(client).build().unwrap();
+    let req_ctx = Arc::new(RequestContext::from(&server_ctx));
+    let request = async_graphql::Request::new("{ post { user { path post { path } } } }").data(req_ctx);
+    let response = server_ctx.schema.execute(request).await;
+    assert_eq!(
+      response.data.into_json().unwrap(),
+      serde_json::json!({"post": {"user": {"path": "/users/1", "post": {"path": "/posts/2"}}}})
+    );
+  }
 }
@@ -4,7 +4,7 @@ use async_graphql::SDLExportOptions;
 /// SDL returned from AsyncSchemaInner isn't standard
 /// We clean it up before returning.
 pub fn print_schema(schema: Schema) -> String {
-  let sdl = schema.sdl_with_options(SDLExportOptions::new().sorted_fields());
+  let sdl = schema.sdl_with_options(SDLExportOptions::new().sorted_fields().include_specified_by());
   let mut result = String::new();
   let mut prev_line_empty = false;
 
@@ -1,13 +1,18 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 
 use derive_setters::Setters;
 use hyper::HeaderMap;
 use reqwest::header::{HeaderName, HeaderValue};
+use sha2::{Digest, Sha256};
 use url::Url;
 
+use crate::config::Encoding;
 use crate::endpoint::Endpoint;
 use crate::has_headers::HasHeaders;
-use crate::mustache::Mustache;
+use crate::has_uploads::{HasUploads, Upload};
+use crate::json_template::JsonTemplate;
+use crate::mustache::{Mustache, Segment};
 use crate::path_string::PathString;
 
 /// RequestTemplate is an extension of a Mustache template.
@@ -20,8 +25,11 @@ pub struct RequestTemplate {
   pub query: Vec<(String, Mustache)>,
   pub method: reqwest::Method,
   pub headers: Vec<(String, Mustache)>,
-  pub body: Option<Mustache>,
+  pub body: Option<JsonTemplate>,
+  pub encoding: Encoding,
   pub endpoint: Endpoint,
+  /// The arguments typed `Upload`, the files they point to are sent as the parts rendered from them.
+  pub upload_args: Vec<String>,
 }
 
 impl RequestTemplate {
@@ -69,7 +77,7 @@ impl RequestTemplate {
   /// Returns true if there are not templates
   pub fn is_const(&self) -> bool {
     self.root_url.is_const()
-      && self.body.as_ref().map_or(true, Mustache::is_const)
+      && self.body.as_ref().map_or(true, JsonTemplate::is_const)
       && self.query.iter().all(|(_, v)| v.is_const())
       && self.headers.iter().all(|(_, v)| v.is_const())
   }
@@ -90,23 +98,74 @@ impl RequestTemplate {
   }
 
   /// Creates a Request for the given context
-  pub fn to_request<C: PathString + HasHeaders>(&self, ctx: &C) -> anyhow::Result<reqwest::Request> {
+  pub fn to_request<C: PathString + HasHeaders + HasUploads>(&self, ctx: &C) -> anyhow::Result<reqwest::Request> {
     // Create url
     let url = self.create_url(ctx)?;
     let method = self.method.clone();
     let mut req = reqwest::Request::new(method, url);
     req = self.set_headers(req, ctx);
-    req = self.set_body(req, ctx);
+    req = self.set_body(req, ctx)?;
 
     Ok(req)
   }
 
-  /// Sets the body for the request
-  fn set_body<C: PathString + HasHeaders>(&self, mut req: reqwest::Request, ctx: &C) -> reqwest::Request {
-    if let Some(body) = &self.body {
-      req.body_mut().replace(body.render(ctx).into());
+  /// Sets the body for the request, along with the `Content-Type` of its encoding.
+  /// Form and multipart bodies always keep theirs, since the boundary of a multipart body is part of it.
+  fn set_body<C: PathString + HasUploads>(
+    &self,
+    mut req: reqwest::Request,
+    ctx: &C,
+  ) -> anyhow::Result<reqwest::Request> {
+    let (content_type, body) = match &self.body {
+      // A string body is sent as rendered text, for compatibility with plain templates
+      Some(JsonTemplate::Mustache(body)) if self.encoding == Encoding::ApplicationJson => {
+        ("application/json".to_string(), Some(body.render(ctx).into_bytes()))
+      }
+      Some(body) => {
+        let uploads = Uploads { files: ctx.uploads(), fields: self.upload_fields() };
+        let (content_type, body) = encode_body(&self.encoding, body.render(ctx), &uploads)?;
+        (content_type, Some(body))
+      }
+      None => (content_type(&self.encoding).to_string(), None),
+    };
+    let content_type = HeaderValue::from_str(&content_type)?;
+    match self.encoding {
+      Encoding::FormUrlEncoded | Encoding::Multipart => {
+        req.headers_mut().insert(reqwest::header::CONTENT_TYPE, content_type);
+      }
+      Encoding::ApplicationJson | Encoding::Text => {
+        req
+          .headers_mut()
+          .entry(reqwest::header::CONTENT_TYPE)
+          .or_insert(content_type);
+      }
+    }
+    if let Some(body) = body {
+      req.body_mut().replace(body.into());
+    }
+    Ok(req)
+  }
+
+  /// The fields of the body rendered from an argument typed `Upload`, whose values are sent as files.
+  fn upload_fields(&self) -> HashSet<&str> {
+    let is_upload = |template: &JsonTemplate| match template {
+      JsonTemplate::Mustache(mustache) => match mustache.segments() {
+        [Segment::Expression(parts)] => match parts.as_slice() {
+          [head, name] => head == "args" && self.upload_args.contains(name),
+          _ => false,
+        },
+        _ => false,
+      },
+      _ => false,
+    };
+    match &self.body {
+      Some(JsonTemplate::Object(fields)) => fields
+        .iter()
+        .filter(|(_, template)| is_upload(template))
+        .map(|(name, _)| name.as_str())
+        .collect(),
+      _ => HashSet::new(),
     }
-    req
   }
 
   /// Sets the headers for the request
@@ -116,12 +175,7 @@ impl RequestTemplate {
       req.headers_mut().extend(headers);
     }
 
-    let headers = req.headers_mut();
-    headers.insert(
-      reqwest::header::CONTENT_TYPE,
-      HeaderValue::from_static("application/json"),
-    );
-    headers.extend(ctx.headers().to_owned());
+    req.headers_mut().extend(ctx.headers().to_owned());
     req
   }
 
@@ -132,7 +186,9 @@ impl RequestTemplate {
       method: reqwest::Method::GET,
       headers: Default::default(),
       body: Default::default(),
+      encoding: Default::default(),
       endpoint: Endpoint::new(root_url.to_string()),
+      upload_args: Default::default(),
     })
   }
 }
@@ -154,15 +210,152 @@ impl TryFrom<Endpoint> for RequestTemplate {
       .collect::<anyhow::Result<Vec<_>>>()?;
 
     let body = if let Some(body) = &endpoint.body {
-      Some(Mustache::parse(body.as_str())?)
+      Some(JsonTemplate::parse(body)?)
     } else {
       None
     };
 
-    Ok(Self { root_url: path, query, method, headers, body, endpoint })
+    let encoding = endpoint.encoding.clone();
+
+    Ok(Self { root_url: path, query, method, headers, body, encoding, endpoint, upload_args: Vec::new() })
   }
 }
 
+fn content_type(encoding: &Encoding) -> &'static str {
+  match encoding {
+    Encoding::ApplicationJson => "application/json",
+    Encoding::FormUrlEncoded => "application/x-www-form-urlencoded",
+    Encoding::Multipart => "multipart/form-data",
+    Encoding::Text => "text/plain",
+  }
+}
+
+/// Serializes a rendered body, returning it along with its `Content-Type`.
+fn encode_body(encoding: &Encoding, body: serde_json::Value, uploads: &Uploads) -> anyhow::Result<(String, Vec<u8>)> {
+  let content_type = content_type(encoding).to_string();
+  match (encoding, body) {
+    (Encoding::ApplicationJson, body) => Ok((content_type, body.to_string().into_bytes())),
+    (Encoding::Text | Encoding::FormUrlEncoded, serde_json::Value::String(body)) => {
+      Ok((content_type, body.into_bytes()))
+    }
+    (Encoding::Text, body) => Ok((content_type, body.to_string().into_bytes())),
+    (Encoding::FormUrlEncoded, serde_json::Value::Object(fields)) => {
+      let pairs = fields
+        .iter()
+        .flat_map(|(name, value)| form_values(value).into_iter().map(move |value| (name, value)))
+        .collect::<Vec<_>>();
+      Ok((content_type, serde_urlencoded::to_string(pairs)?.into_bytes()))
+    }
+    (Encoding::Multipart, serde_json::Value::Object(fields)) => {
+      let parts = fields
+        .iter()
+        .flat_map(|(name, value)| match value {
+          serde_json::Value::Array(items) => items.iter().map(|item| (name, item)).collect(),
+          value => vec![(name, value)],
+        })
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| multipart_part(name, value, uploads))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+      let boundary = multipart_boundary(&parts);
+      let mut body = Vec::new();
+      for part in parts {
+        body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        body.extend(part);
+        body.extend_from_slice(b"\r\n");
+      }
+      body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+      Ok((format!("{content_type}; boundary={boundary}"), body))
+    }
+    (_, body) => Err(anyhow::anyhow!(
+      "can't encode {} as {:?}, expected an object",
+      body,
+      encoding
+    )),
+  }
+}
+
+/// The values a form field is sent with: none for null, one per item for lists, and JSON for objects.
+fn form_values(value: &serde_json::Value) -> Vec<String> {
+  match value {
+    serde_json::Value::Null => vec![],
+    serde_json::Value::String(value) => vec![value.clone()],
+    serde_json::Value::Array(items) => items.iter().flat_map(form_values).collect(),
+    value => vec![value.to_string()],
+  }
+}
+
+/// The files uploaded along with the GraphQL request, and the fields of the body that send them.
+struct Uploads<'a> {
+  files: &'a [Upload],
+  fields: HashSet<&'a str>,
+}
+
+impl Uploads<'_> {
+  /// The file a value of the body points to, if the field it's sent as is rendered from an argument typed `Upload`.
+  fn find(&self, field: &str, value: &serde_json::Value) -> Option<&Upload> {
+    if self.fields.contains(field) {
+      Upload::find(self.files, value.as_str()?)
+    } else {
+      None
+    }
+  }
+}
+
+/// A part of a multipart body, with its headers. Values of upload fields are sent as files.
+fn multipart_part(name: &str, value: &serde_json::Value, uploads: &Uploads) -> anyhow::Result<Vec<u8>> {
+  let upload = uploads.find(name, value);
+  let name = escape_quoted(name);
+  let part = match (upload, value) {
+    (Some(upload), _) => {
+      let content_type = upload.content_type.as_deref().unwrap_or("application/octet-stream");
+      if HeaderValue::from_str(content_type).is_err() {
+        return Err(anyhow::anyhow!(
+          "invalid content type {:?} for the file {:?}",
+          content_type,
+          upload.filename
+        ));
+      }
+      let filename = escape_quoted(&upload.filename);
+      let mut part = format!(
+        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: {content_type}\r\n\r\n"
+      )
+      .into_bytes();
+      part.extend_from_slice(&upload.content);
+      part
+    }
+    (None, serde_json::Value::String(value)) => {
+      format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}").into_bytes()
+    }
+    (None, serde_json::Value::Object(_)) => {
+      format!("Content-Disposition: form-data; name=\"{name}\"\r\nContent-Type: application/json\r\n\r\n{value}")
+        .into_bytes()
+    }
+    (None, value) => format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}").into_bytes(),
+  };
+  Ok(part)
+}
+
+/// Escapes a name quoted in the `Content-Disposition` of a part the way browsers do,
+/// so that it can't end the quoted string, or the headers of the part.
+fn escape_quoted(name: &str) -> String {
+  name.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Derives the boundary from the parts, so it can't occur in them and the same body is always encoded the same
+/// way, which keeps requests deduplicated by the data loader.
+fn multipart_boundary(parts: &[Vec<u8>]) -> String {
+  let mut hasher = Sha256::new();
+  for part in parts {
+    hasher.update(part);
+  }
+  let hash = hasher
+    .finalize()
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect::<String>();
+  format!("tailcall-{}", &hash[..32])
+}
+
 #[cfg(test)]
 mod tests {
   use std::borrow::Cow;
@@ -172,6 +365,11 @@ mod tests {
   use pretty_assertions::assert_eq;
   use serde_json::json;
 
+  use crate::builder::TailcallBuilder;
+  use crate::config::{Config, Encoding};
+  use crate::has_uploads::Upload;
+  use crate::http::{MockHttpClient, Response};
+  use crate::json_template::JsonTemplate;
   use crate::mustache::Mustache;
   use crate::request_template::RequestTemplate;
 
@@ -179,23 +377,32 @@ mod tests {
   struct Context {
     pub value: serde_json::Value,
     pub headers: HeaderMap,
+    pub uploads: Vec<Upload>,
   }
 
   impl Default for Context {
     fn default() -> Self {
-      Self { value: serde_json::Value::Null, headers: HeaderMap::new() }
+      Self { value: serde_json::Value::Null, headers: HeaderMap::new(), uploads: Vec::new() }
     }
   }
   impl crate::path_string::PathString for Context {
     fn path_string<T: AsRef<str>>(&self, parts: &[T]) -> Option<Cow<'_, str>> {
       self.value.path_string(parts)
     }
+    fn path_json<T: AsRef<str>>(&self, parts: &[T]) -> Option<serde_json::Value> {
+      self.value.path_json(parts)
+    }
   }
   impl crate::has_headers::HasHeaders for Context {
     fn headers(&self) -> &HeaderMap {
       &self.headers
     }
   }
+  impl crate::has_uploads::HasUploads for Context {
+    fn uploads(&self) -> &[Upload] {
+      &self.uploads
+    }
+  }
   #[test]
   fn test_url() {
     let tmpl = RequestTemplate::new("http://localhost:3000/").unwrap();
@@ -316,7 +523,7 @@ mod tests {
   fn test_body() {
     let tmpl = RequestTemplate::new("http://localhost:3000")
       .unwrap()
-      .body(Some(Mustache::parse("foo").unwrap()));
+      .body(Some(JsonTemplate::Mustache(Mustache::parse("foo").unwrap())));
     let ctx = Context::default();
     let body = tmpl
       .to_request(&ctx)
@@ -332,7 +539,7 @@ mod tests {
   fn test_body_template() {
     let tmpl = RequestTemplate::new("http://localhost:3000")
       .unwrap()
-      .body(Some(Mustache::parse("{{foo.bar}}").unwrap()));
+      .body(Some(JsonTemplate::Mustache(Mustache::parse("{{foo.bar}}").unwrap())));
     let ctx = Context::default().value(json!({
       "foo": {
         "bar": "baz"
@@ -355,7 +562,7 @@ mod tests {
     let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
       .method(crate::http::Method::POST)
       .headers(headers)
-      .body(Some("foo".into()));
+      .body(Some(json!("foo")));
     let tmpl = RequestTemplate::try_from(endpoint).unwrap();
     let ctx = Context::default();
     let req = tmpl.to_request(&ctx).unwrap();
@@ -373,7 +580,7 @@ mod tests {
       .method(crate::http::Method::POST)
       .query(vec![("foo".to_string(), "{{foo.bar}}".to_string())])
       .headers(headers)
-      .body(Some("{{foo.bar}}".into()));
+      .body(Some(json!("{{foo.bar}}")));
     let tmpl = RequestTemplate::try_from(endpoint).unwrap();
     let ctx = Context::default().value(json!({
       "foo": {
@@ -389,6 +596,147 @@ mod tests {
     assert_eq!(req.url().to_string(), "http://localhost:3000/baz?foo=baz");
   }
 
+  #[test]
+  fn test_from_endpoint_json_body() {
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .body(Some(
+        json!({"id": "{{args.id}}", "input": "{{args.input}}", "title": "Post {{args.id}}"}),
+      ));
+    let tmpl = RequestTemplate::try_from(endpoint).unwrap();
+    let ctx = Context::default().value(json!({"args": {"id": 1, "input": {"name": "foo \"bar\""}}}));
+    let req = tmpl.to_request(&ctx).unwrap();
+    let body: serde_json::Value = serde_json::from_slice(req.body().unwrap().as_bytes().unwrap()).unwrap();
+    assert_eq!(
+      body,
+      json!({"id": 1, "input": {"name": "foo \"bar\""}, "title": "Post 1"})
+    );
+  }
+
+  #[test]
+  fn test_form_url_encoded_body() {
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .encoding(Encoding::FormUrlEncoded)
+      .body(Some(
+        json!({"ids": "{{args.ids}}", "name": "{{args.name}}", "missing": "{{args.missing}}"}),
+      ));
+    let tmpl = RequestTemplate::try_from(endpoint).unwrap();
+    let ctx = Context::default().value(json!({"args": {"ids": [1, 2], "name": "foo & bar"}}));
+    let req = tmpl.to_request(&ctx).unwrap();
+    assert_eq!(
+      req.headers().get("content-type").unwrap(),
+      "application/x-www-form-urlencoded"
+    );
+    let body = req.body().unwrap().as_bytes().unwrap().to_owned();
+    assert_eq!(body, "ids=1&ids=2&name=foo+%26+bar".as_bytes());
+  }
+
+  #[test]
+  fn test_text_body() {
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .encoding(Encoding::Text)
+      .body(Some(json!("{{args.id}}")));
+    let tmpl = RequestTemplate::try_from(endpoint).unwrap();
+    let ctx = Context::default().value(json!({"args": {"id": 1}}));
+    let req = tmpl.to_request(&ctx).unwrap();
+    assert_eq!(req.headers().get("content-type").unwrap(), "text/plain");
+    let body = req.body().unwrap().as_bytes().unwrap().to_owned();
+    assert_eq!(body, "1".as_bytes());
+  }
+
+  #[test]
+  fn test_multipart_body_requires_object() {
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .encoding(Encoding::Multipart)
+      .body(Some(json!("{{args.ids}}")));
+    let tmpl = RequestTemplate::try_from(endpoint).unwrap();
+    let ctx = Context::default().value(json!({"args": {"ids": [1, 2]}}));
+    let error = tmpl.to_request(&ctx).unwrap_err();
+    assert_eq!(error.to_string(), "can't encode [1,2] as Multipart, expected an object");
+  }
+
+  fn multipart_template() -> RequestTemplate {
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .encoding(Encoding::Multipart)
+      .body(Some(json!({"file": "{{args.file}}", "note": "{{args.note}}"})));
+    RequestTemplate::try_from(endpoint)
+      .unwrap()
+      .upload_args(vec!["file".to_string()])
+  }
+
+  fn upload(content_type: &str) -> Upload {
+    Upload {
+      filename: "a\"b\r\n.txt".to_string(),
+      content_type: Some(content_type.to_string()),
+      content: b"hello".to_vec(),
+    }
+  }
+
+  #[test]
+  fn test_multipart_body() {
+    let ctx = Context::default()
+      .value(json!({"args": {"file": "#__graphql_file__:0", "note": "#__graphql_file__:0"}}))
+      .uploads(vec![upload("text/plain")]);
+    let req = multipart_template().to_request(&ctx).unwrap();
+    let content_type = req.headers().get("content-type").unwrap().to_str().unwrap();
+    let boundary = content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+    let body = String::from_utf8(req.body().unwrap().as_bytes().unwrap().to_owned()).unwrap();
+
+    // Only the arguments typed `Upload` are sent as files, and quotes and line breaks are escaped in names
+    let expected = [
+      format!("--{boundary}"),
+      r#"Content-Disposition: form-data; name="file"; filename="a%22b%0D%0A.txt""#.to_string(),
+      "Content-Type: text/plain".to_string(),
+      "".to_string(),
+      "hello".to_string(),
+      format!("--{boundary}"),
+      r#"Content-Disposition: form-data; name="note""#.to_string(),
+      "".to_string(),
+      "#__graphql_file__:0".to_string(),
+      format!("--{boundary}--"),
+      "".to_string(),
+    ]
+    .join("\r\n");
+    assert_eq!(body, expected);
+  }
+
+  #[test]
+  fn test_multipart_body_content_type_override() {
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", "application/vnd.api+json".parse().unwrap());
+    let ctx = Context::default()
+      .value(json!({"args": {"note": "foo"}}))
+      .headers(headers);
+
+    // Neither the template's nor the forwarded headers can drop the boundary of a multipart body
+    let tmpl = multipart_template().headers(vec![("content-type".into(), Mustache::parse("text/plain").unwrap())]);
+    let req = tmpl.to_request(&ctx).unwrap();
+    let content_type = req.headers().get("content-type").unwrap().to_str().unwrap();
+    assert!(content_type.starts_with("multipart/form-data; boundary="));
+
+    let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/".to_string())
+      .method(crate::http::Method::POST)
+      .body(Some(json!({"note": "{{args.note}}"})));
+    let req = RequestTemplate::try_from(endpoint).unwrap().to_request(&ctx).unwrap();
+    assert_eq!(req.headers().get("content-type").unwrap(), "application/vnd.api+json");
+  }
+
+  #[test]
+  fn test_multipart_body_invalid_content_type() {
+    let ctx = Context::default()
+      .value(json!({"args": {"file": "#__graphql_file__:0"}}))
+      .uploads(vec![upload("text/plain\r\nX-Injected: 1")]);
+    let error = multipart_template().to_request(&ctx).unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      r#"invalid content type "text/plain\r\nX-Injected: 1" for the file "a\"b\r\n.txt""#
+    );
+  }
+
   #[test]
   fn test_from_endpoint_template_null_value() {
     let endpoint = crate::endpoint::Endpoint::new("http://localhost:3000/?a={{args.a}}".to_string());
@@ -456,4 +804,89 @@ mod tests {
     let req = tmpl.to_request(&ctx).unwrap();
     assert_eq!(req.headers().get("baz").unwrap(), "qux");
   }
+
+  #[tokio::test]
+  async fn test_multipart_upload() {
+    let config = Config::from_sdl(
+      r#"
+        schema @upstream(baseURL: "http://upstream") {
+          query: Query
+          mutation: Mutation
+        }
+
+        scalar Upload
+
+        type Query {
+          echo: Echo @http(path: "/echo")
+        }
+
+        type Mutation {
+          upload(file: Upload!, name: String): Echo
+            @http(path: "/upload", method: "POST", encoding: MULTIPART, body: {file: "{{args.file}}", name: "{{args.name}}"})
+        }
+
+        type Echo {
+          contentType: String
+          body: String
+        }
+      "#,
+    )
+    .to_result()
+    .unwrap();
+    // Echoes the body of the request, along with its content type
+    let client = MockHttpClient(|req: &reqwest::Request| {
+      let content_type = req.headers().get(reqwest::header::CONTENT_TYPE).unwrap().to_str()?;
+      let body = String::from_utf8_lossy(req.body().and_then(|body| body.as_bytes()).unwrap_or_default());
+      let body = json!({"contentType": content_type, "body": body});
+      Ok(Response::default().body(async_graphql::Value::from_json(body)?))
+    });
+    let service = TailcallBuilder::new(config)
+      .http_client(client)
+      .build_service()
+      .unwrap();
+    let body = [
+      "--boundary",
+      r#"Content-Disposition: form-data; name="operations""#,
+      "",
+      r#"{"query": "mutation($file: Upload!) { upload(file: $file, name: \"notes\") { contentType body } }", "variables": {"file": null}}"#,
+      "--boundary",
+      r#"Content-Disposition: form-data; name="map""#,
+      "",
+      r#"{"0": ["variables.file"]}"#,
+      "--boundary",
+      r#"Content-Disposition: form-data; name="0"; filename="notes.txt""#,
+      "Content-Type: text/plain",
+      "",
+      "hello",
+      "--boundary--",
+      "",
+    ]
+    .join("\r\n");
+    let request = hyper::Request::post("/graphql")
+      .header(hyper::header::CONTENT_TYPE, "multipart/form-data; boundary=boundary")
+      .body(hyper::Body::from(body))
+      .unwrap();
+    let response = service.execute(request).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let upload = &body["data"]["upload"];
+
+    let content_type = upload["contentType"].as_str().unwrap();
+    let boundary = content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+    let expected = [
+      format!("--{boundary}"),
+      r#"Content-Disposition: form-data; name="file"; filename="notes.txt""#.to_string(),
+      "Content-Type: text/plain".to_string(),
+      "".to_string(),
+      "hello".to_string(),
+      format!("--{boundary}"),
+      r#"Content-Disposition: form-data; name="name""#.to_string(),
+      "".to_string(),
+      "notes".to_string(),
+      format!("--{boundary}--"),
+      "".to_string(),
+    ]
+    .join("\r\n");
+    assert_eq!(upload["body"].as_str().unwrap(), expected);
+  }
 }
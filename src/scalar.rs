@@ -0,0 +1,204 @@
+use async_graphql::Value;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Scalars that every schema can use without declaring them, whose values are validated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Scalar {
+  Date,
+  DateTime,
+  Email,
+  Url,
+  Uuid,
+  Int64,
+  PhoneNumber,
+}
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^[A-Za-z0-9.!#$%&'*+/=?^_`{|}~-]+@[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+$").unwrap()
+});
+static UUID: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$").unwrap());
+static PHONE_NUMBER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\+[1-9][0-9]{1,14}$").unwrap());
+static TIME: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^([01][0-9]|2[0-3]):[0-5][0-9]:([0-5][0-9]|60)(\.[0-9]+)?([Zz]|[+-]([01][0-9]|2[0-3]):[0-5][0-9])$")
+    .unwrap()
+});
+
+impl Scalar {
+  pub const ALL: [Scalar; 7] = [
+    Scalar::Date,
+    Scalar::DateTime,
+    Scalar::Email,
+    Scalar::Url,
+    Scalar::Uuid,
+    Scalar::Int64,
+    Scalar::PhoneNumber,
+  ];
+
+  pub fn find(name: &str) -> Option<Scalar> {
+    Scalar::ALL.into_iter().find(|scalar| scalar.name() == name)
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Scalar::Date => "Date",
+      Scalar::DateTime => "DateTime",
+      Scalar::Email => "Email",
+      Scalar::Url => "URL",
+      Scalar::Uuid => "UUID",
+      Scalar::Int64 => "Int64",
+      Scalar::PhoneNumber => "PhoneNumber",
+    }
+  }
+
+  pub fn description(&self) -> &'static str {
+    match self {
+      Scalar::Date => "A date without a time, eg. `2023-12-31`.",
+      Scalar::DateTime => "A date and a time with an offset from UTC, eg. `2023-12-31T23:59:59Z`.",
+      Scalar::Email => "An email address, eg. `john@example.com`.",
+      Scalar::Url => "An absolute URL, eg. `https://example.com/path`.",
+      Scalar::Uuid => "A UUID, eg. `123e4567-e89b-12d3-a456-426614174000`.",
+      Scalar::Int64 => "A 64-bit signed integer.",
+      Scalar::PhoneNumber => "A phone number in the E.164 format, eg. `+14155552671`.",
+    }
+  }
+
+  /// The specification the values of the scalar follow, shown with `@specifiedBy`.
+  pub fn specified_by(&self) -> Option<&'static str> {
+    match self {
+      Scalar::Date | Scalar::DateTime => Some("https://datatracker.ietf.org/doc/html/rfc3339#section-5.6"),
+      Scalar::Email => Some("https://html.spec.whatwg.org/multipage/input.html#valid-e-mail-address"),
+      Scalar::Url => Some("https://url.spec.whatwg.org/"),
+      Scalar::Uuid => Some("https://datatracker.ietf.org/doc/html/rfc4122"),
+      Scalar::Int64 => None,
+      Scalar::PhoneNumber => Some("https://www.itu.int/rec/T-REC-E.164"),
+    }
+  }
+
+  /// The message for values that aren't valid.
+  pub fn expected(&self) -> &'static str {
+    match self {
+      Scalar::Date => "expected a date",
+      Scalar::DateTime => "expected a date-time",
+      Scalar::Email => "expected an email address",
+      Scalar::Url => "expected a URL",
+      Scalar::Uuid => "expected a UUID",
+      Scalar::Int64 => "expected a 64-bit integer",
+      Scalar::PhoneNumber => "expected a phone number",
+    }
+  }
+
+  pub fn validate(&self, value: &Value) -> bool {
+    match (self, value) {
+      (Scalar::Int64, Value::Number(number)) => number.is_i64(),
+      (Scalar::Date, Value::String(text)) => is_date(text),
+      (Scalar::DateTime, Value::String(text)) => match text.split_once(['T', 't']) {
+        Some((date, time)) => is_date(date) && TIME.is_match(time),
+        None => false,
+      },
+      (Scalar::Email, Value::String(text)) => EMAIL.is_match(text),
+      (Scalar::Url, Value::String(text)) => url::Url::parse(text).is_ok(),
+      (Scalar::Uuid, Value::String(text)) => UUID.is_match(text),
+      (Scalar::PhoneNumber, Value::String(text)) => PHONE_NUMBER.is_match(text),
+      _ => false,
+    }
+  }
+}
+
+/// Whether the text is a `YYYY-MM-DD` date that exists.
+fn is_date(text: &str) -> bool {
+  let parts = text.split('-').collect::<Vec<_>>();
+  let [year, month, day] = parts.as_slice() else {
+    return false;
+  };
+  if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+    return false;
+  }
+  let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse::<u32>(), day.parse::<u32>()) else {
+    return false;
+  };
+  let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+  let days = match month {
+    1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+    4 | 6 | 9 | 11 => 30,
+    2 if is_leap_year => 29,
+    2 => 28,
+    _ => return false,
+  };
+  (1..=days).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+  use async_graphql::Value;
+
+  use super::Scalar;
+
+  fn is_valid(scalar: Scalar, value: serde_json::Value) -> bool {
+    scalar.validate(&Value::from_json(value).unwrap())
+  }
+
+  #[test]
+  fn test_find() {
+    assert_eq!(Scalar::find("URL"), Some(Scalar::Url));
+    assert_eq!(Scalar::find("Url"), None);
+    assert_eq!(Scalar::find("String"), None);
+  }
+
+  #[test]
+  fn test_date() {
+    assert!(is_valid(Scalar::Date, "2024-02-29".into()));
+    assert!(!is_valid(Scalar::Date, "2023-02-29".into()));
+    assert!(!is_valid(Scalar::Date, "2023-13-01".into()));
+    assert!(!is_valid(Scalar::Date, "2023-1-01".into()));
+    assert!(!is_valid(Scalar::Date, "+2023-01-01".into()));
+    assert!(!is_valid(Scalar::Date, 20230101.into()));
+  }
+
+  #[test]
+  fn test_date_time() {
+    assert!(is_valid(Scalar::DateTime, "2023-12-31T23:59:59Z".into()));
+    assert!(is_valid(Scalar::DateTime, "2023-12-31t23:59:59.123+05:30".into()));
+    assert!(!is_valid(Scalar::DateTime, "2023-12-31T23:59:59".into()));
+    assert!(!is_valid(Scalar::DateTime, "2023-12-31T24:00:00Z".into()));
+    assert!(!is_valid(Scalar::DateTime, "2023-12-31".into()));
+  }
+
+  #[test]
+  fn test_email() {
+    assert!(is_valid(Scalar::Email, "john.doe+news@example.co.uk".into()));
+    assert!(!is_valid(Scalar::Email, "john@localhost".into()));
+    assert!(!is_valid(Scalar::Email, "john doe@example.com".into()));
+    assert!(!is_valid(Scalar::Email, "@example.com".into()));
+  }
+
+  #[test]
+  fn test_url() {
+    assert!(is_valid(Scalar::Url, "https://example.com/path?q=1".into()));
+    assert!(!is_valid(Scalar::Url, "/path".into()));
+  }
+
+  #[test]
+  fn test_uuid() {
+    assert!(is_valid(Scalar::Uuid, "123e4567-e89b-12d3-a456-426614174000".into()));
+    assert!(!is_valid(Scalar::Uuid, "123e4567e89b12d3a456426614174000".into()));
+  }
+
+  #[test]
+  fn test_int64() {
+    assert!(is_valid(Scalar::Int64, 9_007_199_254_740_993_i64.into()));
+    assert!(is_valid(Scalar::Int64, (-1).into()));
+    assert!(!is_valid(Scalar::Int64, u64::MAX.into()));
+    assert!(!is_valid(Scalar::Int64, 1.5.into()));
+    assert!(!is_valid(Scalar::Int64, "1".into()));
+  }
+
+  #[test]
+  fn test_phone_number() {
+    assert!(is_valid(Scalar::PhoneNumber, "+14155552671".into()));
+    assert!(!is_valid(Scalar::PhoneNumber, "4155552671".into()));
+    assert!(!is_valid(Scalar::PhoneNumber, "+0123".into()));
+  }
+}
@@ -0,0 +1,292 @@
+//! Resolvers backed by WebAssembly modules.
+//!
+//! A module exports its linear `memory`, an `alloc(len: i32) -> i32` function
+//! and the resolver function `(ptr: i32, len: i32) -> i64`. The resolver input
+//! is written as JSON into a buffer obtained from `alloc`, and the resolver
+//! returns the location of its JSON output packed as `(ptr << 32) | len`.
+//!
+//! Each call runs in a fresh instance limited in fuel and memory. Modules may
+//! import a subset of WASI preview1 that gives them no access to the host
+//! filesystem, network or environment.
+
+use std::fmt::Debug;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use async_graphql_value::ConstValue;
+use ring::rand::{SecureRandom, SystemRandom};
+use wasmi::core::Trap;
+use wasmi::{Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+const WASI: &str = "wasi_snapshot_preview1";
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_BADF: i32 = 8;
+const ERRNO_FAULT: i32 = 21;
+
+pub struct WasmPlugin {
+  engine: Engine,
+  module: Module,
+  function: String,
+  limits: WasmLimits,
+}
+
+/// What a single call may use: `fuel` is roughly the number of instructions it executes,
+/// and `memory` the size in bytes of the module's memory.
+#[derive(Clone, Copy, Debug)]
+pub struct WasmLimits {
+  pub fuel: u64,
+  pub memory: usize,
+}
+
+impl Debug for WasmPlugin {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WasmPlugin")
+      .field("function", &self.function)
+      .field("limits", &self.limits)
+      .finish()
+  }
+}
+
+struct State {
+  limits: StoreLimits,
+}
+
+impl WasmPlugin {
+  /// Compiles the module, checking that it exports the resolver ABI and only imports supported WASI functions.
+  pub fn new(bytes: &[u8], function: &str, limits: WasmLimits) -> Result<Self> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, bytes)?;
+
+    for export in ["memory", "alloc", function] {
+      if module.get_export(export).is_none() {
+        return Err(anyhow!("module doesn't export '{}'", export));
+      }
+    }
+    for import in module.imports() {
+      if import.module() != WASI || !WASI_FUNCTIONS.contains(&import.name()) {
+        return Err(anyhow!("unsupported import '{}.{}'", import.module(), import.name()));
+      }
+    }
+
+    Ok(Self { engine, module, function: function.to_string(), limits })
+  }
+
+  /// Runs the resolver function with `input`, returning its output.
+  pub fn call(&self, input: &ConstValue) -> Result<ConstValue> {
+    let limits = StoreLimitsBuilder::new()
+      .memory_size(self.limits.memory)
+      .trap_on_grow_failure(true)
+      .build();
+    let mut store = Store::new(&self.engine, State { limits });
+    store.limiter(|state| &mut state.limits);
+    store.add_fuel(self.limits.fuel).map_err(|e| anyhow!("{}", e))?;
+
+    let instance = link(&self.engine)?
+      .instantiate(&mut store, &self.module)?
+      .start(&mut store)?;
+    let memory = instance
+      .get_memory(&store, "memory")
+      .ok_or(anyhow!("module doesn't export 'memory'"))?;
+    let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+    let function = instance.get_typed_func::<(i32, i32), i64>(&store, &self.function)?;
+
+    let input = serde_json::to_vec(input)?;
+    let len = i32::try_from(input.len())?;
+    let ptr = alloc.call(&mut store, len).map_err(to_error)?;
+    memory
+      .write(&mut store, ptr as u32 as usize, &input)
+      .map_err(|e| anyhow!("{}", e))?;
+
+    let output = function.call(&mut store, (ptr, len)).map_err(to_error)? as u64;
+    let (ptr, len) = ((output >> 32) as usize, (output & 0xffff_ffff) as usize);
+    let output = memory
+      .data(&store)
+      .get(ptr..ptr + len)
+      .ok_or(anyhow!("output is out of the module's memory bounds"))?;
+    Ok(serde_json::from_slice(output)?)
+  }
+}
+
+fn to_error(trap: Trap) -> anyhow::Error {
+  match trap.i32_exit_status() {
+    Some(status) => anyhow!("module exited with status {}", status),
+    None => anyhow!("{}", trap),
+  }
+}
+
+const WASI_FUNCTIONS: &[&str] = &[
+  "args_get",
+  "args_sizes_get",
+  "environ_get",
+  "environ_sizes_get",
+  "clock_time_get",
+  "random_get",
+  "fd_write",
+  "fd_close",
+  "fd_seek",
+  "fd_fdstat_get",
+  "sched_yield",
+  "proc_exit",
+];
+
+/// A WASI preview1 subset with an empty environment and no file descriptors,
+/// except for stdout and stderr which are written to the log.
+fn link(engine: &Engine) -> Result<Linker<State>> {
+  let mut linker = Linker::new(engine);
+  linker
+    .func_wrap(WASI, "args_get", |_: Caller<State>, _: i32, _: i32| ERRNO_SUCCESS)?
+    .func_wrap(WASI, "args_sizes_get", write_zero_sizes)?
+    .func_wrap(WASI, "environ_get", |_: Caller<State>, _: i32, _: i32| ERRNO_SUCCESS)?
+    .func_wrap(WASI, "environ_sizes_get", write_zero_sizes)?
+    .func_wrap(
+      WASI,
+      "clock_time_get",
+      |mut caller: Caller<State>, _: i32, _: i64, time: i32| {
+        let now = SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .map_or(0, |now| now.as_nanos() as u64);
+        write(&mut caller, time, &now.to_le_bytes())
+      },
+    )?
+    .func_wrap(WASI, "random_get", |mut caller: Caller<State>, buf: i32, len: i32| {
+      let mut bytes = vec![0; len as u32 as usize];
+      if SystemRandom::new().fill(&mut bytes).is_err() {
+        return ERRNO_FAULT;
+      }
+      write(&mut caller, buf, &bytes)
+    })?
+    .func_wrap(
+      WASI,
+      "fd_write",
+      |mut caller: Caller<State>, fd: i32, iovs: i32, iovs_len: i32, written: i32| {
+        if fd != 1 && fd != 2 {
+          return ERRNO_BADF;
+        }
+        let Some(output) = read_iovs(&caller, iovs, iovs_len) else {
+          return ERRNO_FAULT;
+        };
+        log::info!("wasm: {}", String::from_utf8_lossy(&output).trim_end());
+        write(&mut caller, written, &(output.len() as u32).to_le_bytes())
+      },
+    )?
+    .func_wrap(WASI, "fd_close", |_: Caller<State>, _: i32| ERRNO_BADF)?
+    .func_wrap(WASI, "fd_seek", |_: Caller<State>, _: i32, _: i64, _: i32, _: i32| {
+      ERRNO_BADF
+    })?
+    .func_wrap(WASI, "fd_fdstat_get", |_: Caller<State>, _: i32, _: i32| ERRNO_BADF)?
+    .func_wrap(WASI, "sched_yield", |_: Caller<State>| ERRNO_SUCCESS)?
+    .func_wrap(WASI, "proc_exit", |_: Caller<State>, status: i32| -> Result<(), Trap> {
+      Err(Trap::i32_exit(status))
+    })?;
+  Ok(linker)
+}
+
+fn memory(caller: &Caller<State>) -> Option<Memory> {
+  caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+fn write(caller: &mut Caller<State>, ptr: i32, bytes: &[u8]) -> i32 {
+  match memory(caller).map(|memory| memory.write(&mut *caller, ptr as u32 as usize, bytes)) {
+    Some(Ok(())) => ERRNO_SUCCESS,
+    _ => ERRNO_FAULT,
+  }
+}
+
+fn write_zero_sizes(mut caller: Caller<State>, count: i32, size: i32) -> i32 {
+  match write(&mut caller, count, &0u32.to_le_bytes()) {
+    ERRNO_SUCCESS => write(&mut caller, size, &0u32.to_le_bytes()),
+    errno => errno,
+  }
+}
+
+fn read_iovs(caller: &Caller<State>, iovs: i32, iovs_len: i32) -> Option<Vec<u8>> {
+  let data = memory(caller)?.data(caller);
+  let mut output = Vec::new();
+  for i in 0..iovs_len as u32 as usize {
+    let iov = iovs as u32 as usize + i * 8;
+    let (ptr, len) = (read_u32(data, iov)? as usize, read_u32(data, iov + 4)? as usize);
+    output.extend_from_slice(data.get(ptr..ptr + len)?);
+  }
+  Some(output)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+  let bytes = data.get(offset..offset + 4)?;
+  Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+  use async_graphql_value::ConstValue;
+
+  use super::{WasmLimits, WasmPlugin};
+
+  const LIMITS: WasmLimits = WasmLimits { fuel: 1_000_000, memory: 1 << 20 };
+
+  /// Echoes its input back, after printing it to stdout.
+  const ECHO: &str = r#"
+    (module
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (global $next (mut i32) (i32.const 1024))
+      (func (export "alloc") (param $len i32) (result i32)
+        (local $ptr i32)
+        (local.set $ptr (global.get $next))
+        (global.set $next (i32.add (global.get $next) (local.get $len)))
+        (local.get $ptr))
+      (func (export "resolve") (param $ptr i32) (param $len i32) (result i64)
+        (i32.store (i32.const 0) (local.get $ptr))
+        (i32.store (i32.const 4) (local.get $len))
+        (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+        (i64.or
+          (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+          (i64.extend_i32_u (local.get $len))))
+      (func (export "spin") (param i32 i32) (result i64)
+        (loop $forever (br $forever))
+        (i64.const 0))
+      (func (export "grow") (param i32 i32) (result i64)
+        (drop (memory.grow (i32.const 32)))
+        (i64.const 0)))
+  "#;
+
+  fn plugin(function: &str) -> anyhow::Result<WasmPlugin> {
+    WasmPlugin::new(&wat::parse_str(ECHO).unwrap(), function, LIMITS)
+  }
+
+  #[test]
+  fn test_call() {
+    let input = ConstValue::from_json(serde_json::json!({"id": 1, "name": "foo"})).unwrap();
+    let output = plugin("resolve").unwrap().call(&input).unwrap();
+    assert_eq!(output, input);
+  }
+
+  #[test]
+  fn test_missing_export() {
+    let error = plugin("unknown").unwrap_err();
+    assert_eq!(error.to_string(), "module doesn't export 'unknown'");
+  }
+
+  #[test]
+  fn test_unsupported_import() {
+    let module = wat::parse_str(
+      r#"(module (import "env" "fetch" (func)) (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+    )
+    .unwrap();
+    let error = WasmPlugin::new(&module, "alloc", LIMITS).unwrap_err();
+    assert_eq!(error.to_string(), "unsupported import 'env.fetch'");
+  }
+
+  #[test]
+  fn test_fuel_limit() {
+    let error = plugin("spin").unwrap().call(&ConstValue::Null).unwrap_err();
+    assert!(error.to_string().contains("fuel"), "{}", error);
+  }
+
+  #[test]
+  fn test_memory_limit() {
+    assert!(plugin("grow").unwrap().call(&ConstValue::Null).is_err());
+  }
+}